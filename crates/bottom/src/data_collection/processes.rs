@@ -31,7 +31,10 @@ cfg_if! {
     }
 }
 
-use std::{borrow::Cow, time::Duration};
+use std::{
+    borrow::Cow,
+    time::{Duration, SystemTime},
+};
 
 use super::{error::CollectionResult, DataCollector};
 
@@ -86,6 +89,31 @@ pub struct ProcessHarvest {
     /// Cumulative process uptime.
     pub time: Duration,
 
+    /// CPU time spent in user and kernel mode. Only collected on Linux, zero elsewhere.
+    pub cpu_time: Duration,
+
+    /// CPU time of children that have exited and were waited for. Only collected on Linux,
+    /// zero elsewhere.
+    pub children_cpu_time: Duration,
+
+    /// When the process was started, in clock ticks since boot on Linux, and in seconds since
+    /// the UNIX epoch elsewhere. Together with the PID, this identifies a process, as PIDs get
+    /// reused.
+    pub start_time: u64,
+
+    /// When the process was started, as wall-clock time. `None` if unknown.
+    pub started_at: Option<SystemTime>,
+
+    /// The nice value, from 19 (lowest priority) to -20 (highest priority). Only collected on
+    /// Linux, 0 elsewhere.
+    pub nice: i32,
+
+    /// The number of threads. Only collected on Linux, 0 elsewhere.
+    pub threads: u64,
+
+    /// The controlling terminal, e.g. `pts/3`. Only collected on Linux.
+    pub tty: Option<String>,
+
     /// This is the *effective* user ID of the process. This is only used on
     /// Unix platforms.
     #[cfg(target_family = "unix")]
@@ -120,7 +148,10 @@ impl ProcessHarvest {
         self.write_bytes_per_sec += rhs.write_bytes_per_sec;
         self.total_read_bytes += rhs.total_read_bytes;
         self.total_write_bytes += rhs.total_write_bytes;
+        self.threads += rhs.threads;
         self.time = self.time.max(rhs.time);
+        self.cpu_time += rhs.cpu_time;
+        self.children_cpu_time += rhs.children_cpu_time;
         #[cfg(feature = "gpu")]
         {
             self.gpu_mem += rhs.gpu_mem;
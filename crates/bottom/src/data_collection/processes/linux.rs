@@ -5,7 +5,8 @@ mod process;
 use std::{
     fs::{self, File},
     io::{BufRead, BufReader},
-    time::Duration,
+    sync::OnceLock,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use hashbrown::HashSet;
@@ -19,6 +20,35 @@ use crate::data_collection::{error::CollectionResult, DataCollector};
 /// If it's equal or greater, then we instead refer to the command for the name.
 const MAX_STAT_NAME_LEN: usize = 15;
 
+/// Boot time in seconds since the UNIX epoch, from `btime` in `/proc/stat`.
+fn boot_time() -> Option<u64> {
+    static BOOT_TIME: OnceLock<Option<u64>> = OnceLock::new();
+    *BOOT_TIME.get_or_init(|| {
+        let stat = fs::read_to_string("/proc/stat").ok()?;
+        stat.lines()
+            .find_map(|line| line.strip_prefix("btime "))?
+            .trim()
+            .parse()
+            .ok()
+    })
+}
+
+fn ticks_to_duration(ticks: u64, ticks_per_sec: u64) -> Duration {
+    match ticks_per_sec {
+        0 => Duration::ZERO,
+        tps => Duration::from_millis(ticks * 1000 / tps),
+    }
+}
+
+/// Converts a start time in clock ticks since boot to wall-clock time.
+fn started_at(start_ticks: u64, ticks_per_sec: u64) -> Option<SystemTime> {
+    if ticks_per_sec == 0 {
+        return None;
+    }
+    let since_boot = Duration::from_millis(start_ticks * 1000 / ticks_per_sec);
+    Some(UNIX_EPOCH + Duration::from_secs(boot_time()?) + since_boot)
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct PrevProcDetails {
     total_read_bytes: u64,
@@ -259,6 +289,22 @@ fn read_proc(
             uid,
             user,
             time,
+            start_time: stat.start_time,
+            started_at: started_at(
+                stat.start_time,
+                rustix::param::clock_ticks_per_second(),
+            ),
+            nice: stat.nice,
+            cpu_time: ticks_to_duration(
+                stat.utime + stat.stime,
+                rustix::param::clock_ticks_per_second(),
+            ),
+            children_cpu_time: ticks_to_duration(
+                stat.cutime + stat.cstime,
+                rustix::param::clock_ticks_per_second(),
+            ),
+            threads: stat.num_threads,
+            tty: tty_name(stat.tty_nr),
             #[cfg(feature = "gpu")]
             gpu_mem: 0,
             #[cfg(feature = "gpu")]
@@ -404,6 +450,15 @@ pub(crate) fn linux_process_data(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_tty_name() {
+        assert_eq!(tty_name(0), None);
+        assert_eq!(tty_name((4 << 8) | 1), Some("tty1".to_string()));
+        assert_eq!(tty_name((4 << 8) | 65), Some("ttyS1".to_string()));
+        assert_eq!(tty_name((136 << 8) | 3), Some("pts/3".to_string()));
+        assert_eq!(tty_name((137 << 8) | 2), Some("pts/258".to_string()));
+    }
+
     #[test]
     fn test_proc_cpu_parse() {
         assert_eq!(
@@ -41,6 +41,9 @@ pub(crate) struct Stat {
     /// The parent process PID.
     pub ppid: Pid,
 
+    /// The controlling terminal, as a device number. 0 if there's none.
+    pub tty_nr: u32,
+
     /// The amount of time this process has been scheduled in user mode in clock
     /// ticks.
     pub utime: u64,
@@ -49,10 +52,24 @@ pub(crate) struct Stat {
     /// clock ticks.
     pub stime: u64,
 
+    /// The amount of time waited-for children have been scheduled in user mode
+    /// in clock ticks.
+    pub cutime: u64,
+
+    /// The amount of time waited-for children have been scheduled in kernel
+    /// mode in clock ticks.
+    pub cstime: u64,
+
     /// The resident set size, or the number of pages the process has in real
     /// memory.
     pub rss: u64,
 
+    /// The nice value, from 19 (lowest priority) to -20 (highest priority).
+    pub nice: i32,
+
+    /// The number of threads.
+    pub num_threads: u64,
+
     /// The start time of the process, represented in clock ticks.
     pub start_time: u64,
 }
@@ -86,15 +103,26 @@ impl Stat {
             .ok_or_else(|| anyhow!("missing state"))?;
         let ppid: Pid = next_part(&mut rest)?.parse()?;
 
-        // Skip 9 fields until utime (pgrp, session, tty_nr, tpgid, flags, minflt,
-        // cminflt, majflt, cmajflt).
-        let mut rest = rest.skip(9);
+        // Skip 2 fields until tty_nr (pgrp, session).
+        let mut rest = rest.skip(2);
+        let tty_nr: u32 = next_part(&mut rest)?.parse::<i32>()? as u32;
+
+        // Skip 6 fields until utime (tpgid, flags, minflt, cminflt, majflt, cmajflt).
+        let mut rest = rest.skip(6);
         let utime: u64 = next_part(&mut rest)?.parse()?;
         let stime: u64 = next_part(&mut rest)?.parse()?;
 
-        // Skip 6 fields until starttime (cutime, cstime, priority, nice, num_threads,
-        // itrealvalue).
-        let mut rest = rest.skip(6);
+        let cutime: u64 = next_part(&mut rest)?.parse()?;
+        let cstime: u64 = next_part(&mut rest)?.parse()?;
+
+        // Skip 1 field until nice (priority).
+        let mut rest = rest.skip(1);
+        let nice: i32 = next_part(&mut rest)?.parse()?;
+
+        let num_threads: u64 = next_part(&mut rest)?.parse()?;
+
+        // Skip 1 field until starttime (itrealvalue).
+        let mut rest = rest.skip(1);
         let start_time: u64 = next_part(&mut rest)?.parse()?;
 
         // Skip one field until rss (vsize)
@@ -105,9 +133,14 @@ impl Stat {
             comm,
             state,
             ppid,
+            tty_nr,
             utime,
             stime,
+            cutime,
+            cstime,
             rss,
+            nice,
+            num_threads,
             start_time,
         })
     }
@@ -119,6 +152,23 @@ impl Stat {
     }
 }
 
+/// Name of a terminal device, as in `ps`, e.g. `pts/3`. `None` if there's no terminal.
+pub(crate) fn tty_name(tty_nr: u32) -> Option<String> {
+    if tty_nr == 0 {
+        return None;
+    }
+    // See `MAJOR` and `MINOR` in linux/kdev_t.h, and devices.txt for the numbers.
+    let major = (tty_nr >> 8) & 0xfff;
+    let minor = (tty_nr & 0xff) | ((tty_nr >> 12) & 0xfff00);
+    Some(match major {
+        4 if minor < 64 => format!("tty{minor}"),
+        4 => format!("ttyS{}", minor - 64),
+        5 if minor == 1 => "console".to_string(),
+        136..=143 => format!("pts/{}", (major - 136) * 256 + minor),
+        _ => format!("{major}:{minor}"),
+    })
+}
+
 /// A wrapper around the data in `/proc/<PID>/io`.
 ///
 /// Note this does not necessarily get all fields, only the ones we use in
@@ -100,6 +100,16 @@ pub(crate) trait UnixProcessExt {
                 } else {
                     Duration::from_secs(process_val.run_time())
                 },
+                start_time: process_val.start_time(),
+                started_at: match process_val.start_time() {
+                    0 => None,
+                    secs => Some(std::time::UNIX_EPOCH + Duration::from_secs(secs)),
+                },
+                nice: 0,
+                cpu_time: Duration::ZERO,
+                children_cpu_time: Duration::ZERO,
+                threads: 0,
+                tty: None,
                 #[cfg(feature = "gpu")]
                 gpu_mem: 0,
                 #[cfg(feature = "gpu")]
@@ -13,7 +13,7 @@ use crate::{
 };
 
 #[derive(Debug)]
-pub(crate) struct QueryError {
+pub struct QueryError {
     reason: Cow<'static, str>,
 }
 
@@ -74,7 +74,7 @@ const AND_LIST: [&str; 2] = ["and", "&&"];
 /// adjacent non-prefixed or quoted elements after splitting to treat as process
 /// names. Furthermore, we want to support boolean joiners like AND and OR, and
 /// brackets.
-pub(crate) fn parse_query(
+pub fn parse_query(
     search_query: &str, is_searching_whole_word: bool, is_ignoring_case: bool,
     is_searching_with_regex: bool,
 ) -> QueryResult<ProcessQuery> {
@@ -164,8 +164,26 @@ pub(crate) fn parse_query(
         Ok(And { lhs, rhs })
     }
 
+    /// Parses a numerical value. A unit glued to the number (like `1GB`) is
+    /// split off and put back in front of the queue, to be handled by
+    /// [`process_prefix_units`].
     #[inline]
-    fn process_prefix_units(query: &mut VecDeque<String>, value: &mut f64) {
+    fn parse_value(value: &str, query: &mut VecDeque<String>) -> Option<f64> {
+        if let Ok(value) = value.parse::<f64>() {
+            return Some(value);
+        }
+
+        let unit_start = value.find(|c: char| c.is_ascii_alphabetic())?;
+        let (number, unit) = value.split_at(unit_start);
+        let number = number.parse::<f64>().ok()?;
+        query.push_front(unit.to_string());
+
+        Some(number)
+    }
+
+    /// Returns whether a unit was found and consumed.
+    #[inline]
+    fn process_prefix_units(query: &mut VecDeque<String>, value: &mut f64) -> bool {
         // If no unit, assume base.
         //
         // Furthermore, base must be PEEKED at initially, and will
@@ -198,8 +216,12 @@ pub(crate) fn parse_query(
                 query.pop_front();
             } else if potential_unit.eq_ignore_ascii_case("b") {
                 query.pop_front();
+            } else {
+                return false;
             }
+            return true;
         }
+        false
     }
 
     fn process_prefix(query: &mut VecDeque<String>, inside_quotation: bool) -> QueryResult<Prefix> {
@@ -424,7 +446,7 @@ pub(crate) fn parse_query(
                             if content == "=" {
                                 condition = Some(QueryComparison::Equal);
                                 if let Some(queue_next) = query.pop_front() {
-                                    value = queue_next.parse::<f64>().ok();
+                                    value = parse_value(&queue_next, query);
                                 } else {
                                     return Err(QueryError::missing_value());
                                 }
@@ -438,7 +460,7 @@ pub(crate) fn parse_query(
                                             QueryComparison::LessOrEqual
                                         });
                                         if let Some(queue_next_next) = query.pop_front() {
-                                            value = queue_next_next.parse::<f64>().ok();
+                                            value = parse_value(&queue_next_next, query);
                                         } else {
                                             return Err(QueryError::missing_value());
                                         }
@@ -448,7 +470,7 @@ pub(crate) fn parse_query(
                                         } else {
                                             QueryComparison::Less
                                         });
-                                        value = queue_next.parse::<f64>().ok();
+                                        value = parse_value(&queue_next, query);
                                     }
                                 } else {
                                     return Err(QueryError::missing_value());
@@ -463,20 +485,28 @@ pub(crate) fn parse_query(
 
                                     let mut value = read_value;
 
-                                    match prefix_type {
+                                    let prefix_type = match prefix_type {
                                         PrefixType::MemBytes
                                         | PrefixType::Rps
                                         | PrefixType::Wps
                                         | PrefixType::TRead
                                         | PrefixType::TWrite => {
                                             process_prefix_units(query, &mut value);
+                                            prefix_type
                                         }
                                         #[cfg(feature = "gpu")]
                                         PrefixType::GMem => {
                                             process_prefix_units(query, &mut value);
+                                            prefix_type
                                         }
-                                        _ => {}
-                                    }
+                                        // Something like `mem > 1GB` is about bytes, not percentage.
+                                        PrefixType::PMem
+                                            if process_prefix_units(query, &mut value) =>
+                                        {
+                                            PrefixType::MemBytes
+                                        }
+                                        _ => prefix_type,
+                                    };
 
                                     return Ok(Prefix {
                                         or: None,
@@ -553,7 +583,7 @@ impl ProcessQuery {
         Ok(())
     }
 
-    pub(crate) fn check(&self, process: &ProcessHarvest, is_using_command: bool) -> bool {
+    pub fn check(&self, process: &ProcessHarvest, is_using_command: bool) -> bool {
         self.query
             .iter()
             .all(|ok| ok.check(process, is_using_command))
@@ -939,3 +969,51 @@ struct TimeQuery {
     condition: QueryComparison,
     duration: Duration,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn process(mem_usage_bytes: u64, mem_usage_percent: f32) -> ProcessHarvest {
+        ProcessHarvest {
+            mem_usage_bytes,
+            mem_usage_percent,
+            ..Default::default()
+        }
+    }
+
+    fn matches(query: &str, process: &ProcessHarvest) -> bool {
+        parse_query(query, false, true, false)
+            .unwrap()
+            .check(process, false)
+    }
+
+    #[test]
+    fn mem_with_unit_compares_bytes() {
+        let big = process(2_000_000_000, 1.0);
+        let small = process(500_000_000, 90.0);
+
+        for query in ["mem > 1GB", "mem > 1 GB", "mem > 1gb", "mem >= 1GB"] {
+            assert!(matches(query, &big), "{query}");
+            assert!(!matches(query, &small), "{query}");
+        }
+    }
+
+    #[test]
+    fn mem_without_unit_compares_percentage() {
+        let big = process(2_000_000_000, 1.0);
+        let small = process(500_000_000, 90.0);
+
+        assert!(!matches("mem > 50", &big));
+        assert!(matches("mem > 50", &small));
+    }
+
+    #[test]
+    fn glued_units_are_parsed() {
+        let process = process(2 * 1024 * 1024, 1.0);
+
+        assert!(matches("memb > 1MiB", &process));
+        assert!(!matches("memb > 2MiB", &process));
+        assert!(matches("memb = 2MiB", &process));
+    }
+}
@@ -0,0 +1,171 @@
+//! CPU affinity, see `sched_setaffinity(2)`.
+
+use rustix::process::{CpuSet, Pid, sched_getaffinity, sched_setaffinity};
+use std::fmt;
+use std::sync::LazyLock;
+
+/// Number of CPUs in the system, cached as it's needed for every row.
+///
+/// Unlike `num_cpus::get`, this doesn't depend on killa's own affinity or cgroup CPU quota.
+pub fn num_cpus() -> usize {
+    static NUM_CPUS: LazyLock<usize> = LazyLock::new(|| {
+        // SAFETY: sysconf has no memory safety requirements.
+        match unsafe { libc::sysconf(libc::_SC_NPROCESSORS_CONF) } {
+            n if n > 0 => n as usize,
+            _ => num_cpus::get(),
+        }
+    });
+    *NUM_CPUS
+}
+
+/// CPUs a process is allowed to run on.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CpuList {
+    /// Sorted and without duplicates.
+    cpus: Vec<usize>,
+}
+
+impl CpuList {
+    pub fn get(pid: i32) -> std::io::Result<Self> {
+        let set = sched_getaffinity(Some(Self::pid(pid)))?;
+        let cpus = (0..num_cpus().min(CpuSet::MAX_CPU))
+            .filter(|cpu| set.is_set(*cpu))
+            .collect();
+        Ok(Self { cpus })
+    }
+
+    /// Sets affinity of the main thread only, which is also inherited by threads created later.
+    pub fn set(&self, pid: i32) -> std::io::Result<()> {
+        let mut set = CpuSet::new();
+        for cpu in &self.cpus {
+            set.set(*cpu);
+        }
+        Ok(sched_setaffinity(Some(Self::pid(pid)), &set)?)
+    }
+
+    /// Sets affinity of every thread in `/proc/<pid>/task`.
+    pub fn set_all_threads(&self, pid: i32) -> std::io::Result<()> {
+        for entry in std::fs::read_dir(format!("/proc/{pid}/task"))? {
+            let tid = entry?.file_name().to_string_lossy().parse::<i32>();
+            match tid.map(|tid| self.set(tid)) {
+                Ok(Ok(())) => {}
+                // Thread exited in the meantime.
+                Ok(Err(err)) if err.raw_os_error() == Some(libc::ESRCH) => {}
+                Ok(Err(err)) => return Err(err),
+                Err(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn pid(pid: i32) -> Pid {
+        Pid::from_raw(pid).expect("pid in table should be valid")
+    }
+
+    /// Parses a list like `taskset --cpu-list`, e.g. `0-3,8`. A leading `!` selects all CPUs
+    /// except the listed ones, e.g. `!0`.
+    pub fn parse(s: &str, num_cpus: usize) -> Result<Self, String> {
+        let (is_negated, s) = match s.trim().strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+
+        let mut cpus = vec![];
+        for item in s.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            let parse_cpu = |cpu: &str| match cpu.trim().parse::<usize>() {
+                Ok(cpu) if cpu < num_cpus => Ok(cpu),
+                Ok(cpu) => Err(format!(
+                    "CPU {cpu} doesn't exist, there are {num_cpus} CPUs"
+                )),
+                Err(_) => Err(format!("invalid CPU '{}'", cpu.trim())),
+            };
+            match item.split_once('-') {
+                Some((first, last)) => {
+                    let (first, last) = (parse_cpu(first)?, parse_cpu(last)?);
+                    if first > last {
+                        return Err(format!("invalid range '{item}'"));
+                    }
+                    cpus.extend(first..=last);
+                }
+                None => cpus.push(parse_cpu(item)?),
+            }
+        }
+
+        // `!` alone would silently mean all CPUs.
+        if is_negated && cpus.is_empty() {
+            return Err("no CPUs to exclude".to_string());
+        }
+        if is_negated {
+            cpus = (0..num_cpus).filter(|cpu| !cpus.contains(cpu)).collect();
+        }
+        cpus.sort_unstable();
+        cpus.dedup();
+        if cpus.is_empty() {
+            return Err("no CPUs selected".to_string());
+        }
+        Ok(Self { cpus })
+    }
+
+    pub fn count(&self) -> usize {
+        self.cpus.len()
+    }
+
+    /// Allowed to run on any of the first `num_cpus` CPUs.
+    pub fn is_all(&self, num_cpus: usize) -> bool {
+        (0..num_cpus).all(|cpu| self.cpus.binary_search(&cpu).is_ok())
+    }
+}
+
+impl fmt::Display for CpuList {
+    /// Formats CPUs as comma separated ranges, e.g. `0-3,8`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut ranges: Vec<(usize, usize)> = vec![];
+        for cpu in &self.cpus {
+            match ranges.last_mut() {
+                Some((_, last)) if *last + 1 == *cpu => *last = *cpu,
+                _ => ranges.push((*cpu, *cpu)),
+            }
+        }
+        for (i, (first, last)) in ranges.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            match first == last {
+                true => write!(f, "{first}")?,
+                false => write!(f, "{first}-{last}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_list() {
+        let parse = |s| CpuList::parse(s, 8).map(|list| list.to_string());
+        assert_eq!(parse("0-3"), Ok("0-3".to_string()));
+        assert_eq!(parse("5, 0-2,1"), Ok("0-2,5".to_string()));
+        assert_eq!(parse("!0"), Ok("1-7".to_string()));
+        assert_eq!(parse("!0-1,7"), Ok("2-6".to_string()));
+        assert!(parse("8").is_err());
+        assert!(parse("3-1").is_err());
+        assert!(parse("a").is_err());
+        assert!(parse("").is_err());
+        assert!(parse("!0-7").is_err());
+        assert!(parse("!").is_err());
+        assert!(parse(" ! ").is_err());
+
+        assert!(CpuList::parse("0-7", 8).unwrap().is_all(8));
+        assert!(!CpuList::parse("1-7", 8).unwrap().is_all(8));
+    }
+
+    #[test]
+    fn test_num_cpus_ignores_own_affinity() {
+        let own = CpuList::get(std::process::id() as i32).unwrap();
+        assert!(num_cpus() >= own.count());
+        assert!(own.cpus.iter().all(|cpu| *cpu < num_cpus()));
+    }
+}
@@ -0,0 +1,153 @@
+//! Acting on whole cgroup v2 groups, e.g. `app-*.scope` of an app started by the desktop.
+
+use std::io;
+use std::path::PathBuf;
+
+/// Mount point of the cgroup v2 hierarchy.
+#[derive(Clone, Debug)]
+pub struct CgroupFs {
+    root: PathBuf,
+}
+
+impl Default for CgroupFs {
+    fn default() -> Self {
+        Self::new("/sys/fs/cgroup")
+    }
+}
+
+impl CgroupFs {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Path of the cgroup v2 group of a process, relative to the cgroupfs root,
+    /// e.g. `/user.slice/user-1000.slice/user@1000.service/app.slice/app-firefox.scope`.
+    pub fn process_cgroup(pid: i32) -> io::Result<Option<String>> {
+        let content = std::fs::read_to_string(format!("/proc/{pid}/cgroup"))?;
+        Ok(parse_proc_cgroup(&content))
+    }
+
+    fn dir(&self, cgroup: &str) -> io::Result<PathBuf> {
+        let relative = cgroup.trim_start_matches('/');
+        // Root group can't be killed or frozen, and `..` would escape the hierarchy.
+        if relative.is_empty() || relative.split('/').any(|part| part == "..") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("refusing to act on cgroup '{cgroup}'"),
+            ));
+        }
+        Ok(self.root.join(relative))
+    }
+
+    /// Pids of all processes in the group, including nested groups.
+    pub fn members(&self, cgroup: &str) -> io::Result<Vec<i32>> {
+        let mut pids = vec![];
+        let mut dirs = vec![self.dir(cgroup)?];
+        while let Some(dir) = dirs.pop() {
+            let procs = std::fs::read_to_string(dir.join("cgroup.procs"))?;
+            pids.extend(
+                procs
+                    .lines()
+                    .filter_map(|line| line.trim().parse::<i32>().ok()),
+            );
+            for entry in std::fs::read_dir(&dir)? {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() {
+                    dirs.push(entry.path());
+                }
+            }
+        }
+        Ok(pids)
+    }
+
+    /// Kills all processes in the group and nested groups at once, so that none of them can
+    /// fork away in the meantime.
+    pub fn kill(&self, cgroup: &str) -> io::Result<()> {
+        std::fs::write(self.dir(cgroup)?.join("cgroup.kill"), "1")
+    }
+
+    /// Freezes or thaws all processes in the group and nested groups.
+    pub fn set_frozen(&self, cgroup: &str, frozen: bool) -> io::Result<()> {
+        let value = if frozen { "1" } else { "0" };
+        std::fs::write(self.dir(cgroup)?.join("cgroup.freeze"), value)
+    }
+}
+
+/// Whether `cgroup` is `ancestor` itself or nested in it.
+pub fn is_within(cgroup: &str, ancestor: &str) -> bool {
+    let ancestor = ancestor.trim_end_matches('/');
+    cgroup
+        .strip_prefix(ancestor)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Extracts the cgroup v2 path from `/proc/<pid>/cgroup` content. cgroup v1 entries are ignored.
+fn parse_proc_cgroup(content: &str) -> Option<String> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(|path| path.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_cgroup() {
+        let content = "12:cpuset:/\n0::/user.slice/app.slice/app-firefox.scope\n";
+        assert_eq!(
+            parse_proc_cgroup(content).as_deref(),
+            Some("/user.slice/app.slice/app-firefox.scope")
+        );
+        assert_eq!(parse_proc_cgroup("12:cpuset:/\n"), None);
+    }
+
+    #[test]
+    fn test_is_within() {
+        assert!(is_within("/app.slice/foo.scope", "/app.slice/foo.scope"));
+        assert!(is_within(
+            "/app.slice/foo.scope/nested",
+            "/app.slice/foo.scope"
+        ));
+        assert!(is_within("/app.slice/foo.scope", "/app.slice/"));
+        assert!(is_within("/app.slice/foo.scope", "/"));
+        assert!(!is_within("/app.slice/foo.scope2", "/app.slice/foo.scope"));
+        assert!(!is_within("/app.slice", "/app.slice/foo.scope"));
+    }
+
+    #[test]
+    fn test_cgroup_actions() {
+        let root = std::env::temp_dir().join(format!("killa-cgroup-test-{}", std::process::id()));
+        let scope = root.join("app.slice/app-foo.scope");
+        std::fs::create_dir_all(scope.join("nested")).unwrap();
+        std::fs::write(scope.join("cgroup.procs"), "10\n11\n").unwrap();
+        std::fs::write(scope.join("nested/cgroup.procs"), "12\n").unwrap();
+
+        let fs = CgroupFs::new(&root);
+        let mut members = fs.members("/app.slice/app-foo.scope").unwrap();
+        members.sort_unstable();
+        assert_eq!(members, vec![10, 11, 12]);
+
+        fs.kill("/app.slice/app-foo.scope").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(scope.join("cgroup.kill")).unwrap(),
+            "1"
+        );
+        fs.set_frozen("/app.slice/app-foo.scope", true).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(scope.join("cgroup.freeze")).unwrap(),
+            "1"
+        );
+        fs.set_frozen("/app.slice/app-foo.scope", false).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(scope.join("cgroup.freeze")).unwrap(),
+            "0"
+        );
+
+        assert!(fs.kill("/").is_err());
+        assert!(fs.kill("/app.slice/../..").is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
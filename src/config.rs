@@ -0,0 +1,109 @@
+//! Settings saved between runs, in `$XDG_CONFIG_HOME/killa` (`~/.config/killa` by default).
+
+use crate::ui::{Column, ColumnKind};
+use std::path::PathBuf;
+
+fn config_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("killa"))
+}
+
+pub fn columns_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("columns"))
+}
+
+/// Columns saved with [`save_columns`]. `None` if there's no config yet.
+pub fn load_columns() -> Option<Vec<Column>> {
+    let content = std::fs::read_to_string(columns_path()?).ok()?;
+    let columns = parse_columns(&content);
+    (!columns.is_empty()).then_some(columns)
+}
+
+pub fn save_columns(columns: &[Column]) -> std::io::Result<()> {
+    let path = columns_path().ok_or_else(|| std::io::Error::other("$HOME is not set"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, format_columns(columns))
+}
+
+const COLUMNS_HEADER: &str = "\
+# Columns of the process table, in order: `<column> [width]`.
+# Width is a share of the table width, relative to other columns.
+# Available columns: ";
+
+/// Parses lines like `cpu 0.06`. Unknown columns and duplicates are skipped, and a missing or
+/// invalid width means the default one.
+fn parse_columns(content: &str) -> Vec<Column> {
+    let mut columns: Vec<Column> = vec![];
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let Some(kind) = parts.next().and_then(ColumnKind::from_id) else {
+            continue;
+        };
+        if columns.iter().any(|column| column.kind == kind) {
+            continue;
+        }
+        let mut column = Column::new(kind);
+        if let Some(width) = parts.next().and_then(|width| width.parse::<f32>().ok())
+            && width.is_finite()
+        {
+            column.width = width.clamp(Column::MIN_WIDTH, Column::MAX_WIDTH);
+        }
+        columns.push(column);
+    }
+    columns
+}
+
+fn format_columns(columns: &[Column]) -> String {
+    let available: Vec<_> = ColumnKind::ALL.iter().map(ColumnKind::id).collect();
+    let mut content = format!("{COLUMNS_HEADER}{}\n", available.join(", "));
+    for column in columns {
+        content.push_str(&format!("{} {:.3}\n", column.kind.id(), column.width));
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columns_roundtrip() {
+        let mut columns = Column::defaults();
+        columns.swap(0, 1);
+        columns[0].width = 0.125;
+        assert_eq!(parse_columns(&format_columns(&columns)), columns);
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        let content = "\
+# comment
+pid
+bogus 0.1
+cpu 100
+cpu 0.1
+memory abc
+";
+        let columns = parse_columns(content);
+        assert_eq!(
+            columns,
+            vec![
+                Column::new(ColumnKind::Pid),
+                Column {
+                    kind: ColumnKind::Cpu,
+                    width: Column::MAX_WIDTH,
+                },
+                Column::new(ColumnKind::Memory),
+            ]
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Everything `/proc/<pid>` tells about a process, shown in the details panel.
+
+use crate::sockets::SocketTable;
+use bytesize::ByteSize;
+use chrono::{DateTime, Local};
+use procfs::WithCurrentSystemInfo;
+use procfs::process::{FDTarget, LimitValue, Process};
+
+/// Limit on the length of the parent chain, in case of a cycle.
+const MAX_PARENTS: usize = 64;
+
+#[derive(Clone, Debug)]
+pub struct ProcessDetails {
+    pub pid: i32,
+    /// Details are read once, as some are slow to get, e.g. sockets. Refreshed on demand.
+    pub read_at: DateTime<Local>,
+    pub sections: Vec<DetailsSection>,
+}
+
+/// Group of related facts, e.g. memory usage.
+#[derive(Clone, Debug)]
+pub struct DetailsSection {
+    pub title: &'static str,
+    /// Label and value pairs, in display order. Values with empty label take the whole width.
+    /// Reading a section fails e.g. for environment of processes of other users.
+    pub entries: Result<Vec<(String, String)>, String>,
+}
+
+impl ProcessDetails {
+    pub fn read(pid: i32) -> Self {
+        let sections = match Process::new(pid) {
+            Ok(ps) => vec![
+                section("Process", || general(&ps)),
+                section("Memory", || memory(&ps)),
+                section("Paths", || paths(&ps)),
+                section("Arguments", || arguments(&ps)),
+                section("Environment", || environment(&ps)),
+                section("File descriptors", || file_descriptors(&ps)),
+                section("Limits", || limits(&ps)),
+                section("Cgroup", || cgroup(&ps)),
+                section("Namespaces", || namespaces(&ps)),
+            ],
+            Err(err) => vec![DetailsSection {
+                title: "Process",
+                entries: Err(err.to_string()),
+            }],
+        };
+        Self {
+            pid,
+            read_at: Local::now(),
+            sections,
+        }
+    }
+}
+
+fn section(
+    title: &'static str,
+    read: impl FnOnce() -> procfs::ProcResult<Vec<(String, String)>>,
+) -> DetailsSection {
+    DetailsSection {
+        title,
+        entries: read().map_err(|err| err.to_string()),
+    }
+}
+
+fn entry(label: &str, value: impl ToString) -> (String, String) {
+    (label.to_string(), value.to_string())
+}
+
+fn general(ps: &Process) -> procfs::ProcResult<Vec<(String, String)>> {
+    let status = ps.status()?;
+    let stat = ps.stat()?;
+
+    let mut entries = vec![
+        entry("Name", &status.name),
+        entry("PID", ps.pid()),
+        entry("Parents", parent_chain(stat.ppid)),
+        entry("State", &status.state),
+        entry(
+            "UID",
+            format!("{} (effective {})", status.ruid, status.euid),
+        ),
+        entry(
+            "GID",
+            format!("{} (effective {})", status.rgid, status.egid),
+        ),
+        entry("Threads", status.threads),
+    ];
+    if let Ok(started) = stat.starttime().get() {
+        entries.push(entry("Started", started.format("%Y-%m-%d %H:%M:%S")));
+    }
+    if let Some(switches) = status.voluntary_ctxt_switches {
+        entries.push(entry("Voluntary ctx switches", switches));
+    }
+    if let Some(switches) = status.nonvoluntary_ctxt_switches {
+        entries.push(entry("Involuntary ctx switches", switches));
+    }
+    Ok(entries)
+}
+
+/// Names and pids of ancestors, closest first, e.g. `bash (1234) ← systemd (1)`.
+fn parent_chain(mut ppid: i32) -> String {
+    let mut parents = vec![];
+    while ppid > 0 && parents.len() < MAX_PARENTS {
+        match Process::new(ppid).and_then(|ps| ps.stat()) {
+            Ok(stat) => {
+                parents.push(format!("{} ({ppid})", stat.comm));
+                ppid = stat.ppid;
+            }
+            Err(_) => {
+                parents.push(format!("? ({ppid})"));
+                break;
+            }
+        }
+    }
+    parents.join(" ← ")
+}
+
+fn memory(ps: &Process) -> procfs::ProcResult<Vec<(String, String)>> {
+    let status = ps.status()?;
+    // Kernel threads don't have any of these.
+    let fields = [
+        ("Resident", status.vmrss),
+        ("Resident peak (VmHWM)", status.vmhwm),
+        ("Virtual peak (VmPeak)", status.vmpeak),
+        ("Swap", status.vmswap),
+    ];
+    Ok(fields
+        .into_iter()
+        .filter_map(|(label, kb)| Some(entry(label, ByteSize::kib(kb?))))
+        .collect())
+}
+
+fn paths(ps: &Process) -> procfs::ProcResult<Vec<(String, String)>> {
+    let describe = |path: procfs::ProcResult<std::path::PathBuf>| match path {
+        Ok(path) => path.display().to_string(),
+        Err(err) => err.to_string(),
+    };
+    Ok(vec![
+        entry("Executable", describe(ps.exe())),
+        entry("Working dir", describe(ps.cwd())),
+    ])
+}
+
+fn arguments(ps: &Process) -> procfs::ProcResult<Vec<(String, String)>> {
+    Ok(ps
+        .cmdline()?
+        .into_iter()
+        .map(|arg| (String::new(), arg))
+        .collect())
+}
+
+fn environment(ps: &Process) -> procfs::ProcResult<Vec<(String, String)>> {
+    let mut vars: Vec<_> = ps
+        .environ()?
+        .into_iter()
+        .map(|(key, value)| {
+            let var = format!("{}={}", key.to_string_lossy(), value.to_string_lossy());
+            (String::new(), var)
+        })
+        .collect();
+    vars.sort_unstable();
+    Ok(vars)
+}
+
+fn file_descriptors(ps: &Process) -> procfs::ProcResult<Vec<(String, String)>> {
+    let mut fds: Vec<_> = ps.fd()?.flatten().collect();
+    fds.sort_unstable_by_key(|fd| fd.fd);
+    let sockets = SocketTable::read(ps);
+
+    let (mut files, mut socket_count, mut pipes, mut other) = (0, 0, 0, 0);
+    let targets: Vec<_> = fds
+        .iter()
+        .map(|fd| {
+            let target = match &fd.target {
+                FDTarget::Path(path) => {
+                    files += 1;
+                    path.display().to_string()
+                }
+                FDTarget::Socket(inode) => {
+                    socket_count += 1;
+                    match sockets.get(*inode) {
+                        Some(socket) => socket.to_string(),
+                        // e.g. netlink, or a socket from another network namespace
+                        None => format!("socket:[{inode}]"),
+                    }
+                }
+                FDTarget::Pipe(inode) => {
+                    pipes += 1;
+                    format!("pipe:[{inode}]")
+                }
+                FDTarget::Net(inode) => {
+                    other += 1;
+                    format!("net:[{inode}]")
+                }
+                FDTarget::AnonInode(kind) => {
+                    other += 1;
+                    format!("anon_inode:{kind}")
+                }
+                FDTarget::MemFD(name) => {
+                    other += 1;
+                    format!("memfd:{name}")
+                }
+                FDTarget::Other(kind, inode) => {
+                    other += 1;
+                    format!("{kind}:[{inode}]")
+                }
+            };
+            entry(&fd.fd.to_string(), target)
+        })
+        .collect();
+
+    let limit = match ps.limits()?.max_open_files.soft_limit {
+        LimitValue::Unlimited => "unlimited".to_string(),
+        LimitValue::Value(limit) => limit.to_string(),
+    };
+    let mut entries = vec![
+        entry("Open", format!("{} of {limit}", fds.len())),
+        entry(
+            "By type",
+            format!("{files} files, {socket_count} sockets, {pipes} pipes, {other} other"),
+        ),
+    ];
+    entries.extend(targets);
+    Ok(entries)
+}
+
+fn limits(ps: &Process) -> procfs::ProcResult<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(format!("/proc/{}/limits", ps.pid()))?;
+    Ok(parse_limits(&content))
+}
+
+/// Parses the `/proc/<pid>/limits` table into names and `soft / hard units` values.
+fn parse_limits(content: &str) -> Vec<(String, String)> {
+    // Names contain spaces, but they're padded to a fixed width, same as the "Limit" header.
+    const NAME_WIDTH: usize = 26;
+
+    content
+        .lines()
+        .skip(1)
+        .filter(|line| line.len() > NAME_WIDTH)
+        .map(|line| {
+            let (name, rest) = line.split_at(NAME_WIDTH);
+            let mut values = rest.split_whitespace();
+            let soft = values.next().unwrap_or_default();
+            let hard = values.next().unwrap_or_default();
+            let units = values.next().unwrap_or_default();
+            let value = format!("{soft} / {hard} {units}");
+            (name.trim().to_string(), value.trim().to_string())
+        })
+        .collect()
+}
+
+fn cgroup(ps: &Process) -> procfs::ProcResult<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(format!("/proc/{}/cgroup", ps.pid()))?;
+    Ok(content
+        .lines()
+        .map(|line| (String::new(), line.to_string()))
+        .collect())
+}
+
+fn namespaces(ps: &Process) -> procfs::ProcResult<Vec<(String, String)>> {
+    let mut namespaces: Vec<_> = ps
+        .namespaces()?
+        .0
+        .into_values()
+        .map(|ns| entry(&ns.ns_type.to_string_lossy(), ns.identifier))
+        .collect();
+    namespaces.sort_unstable();
+    Ok(namespaces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_limits() {
+        let content = "\
+Limit                     Soft Limit           Hard Limit           Units
+Max cpu time              unlimited            unlimited            seconds
+Max open files            1024                 524288               files
+";
+        assert_eq!(
+            parse_limits(content),
+            vec![
+                entry("Max cpu time", "unlimited / unlimited seconds"),
+                entry("Max open files", "1024 / 524288 files"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_own_details() {
+        let details = ProcessDetails::read(std::process::id() as i32);
+        let titles: Vec<_> = details.sections.iter().map(|s| s.title).collect();
+        assert_eq!(titles[0], "Process");
+        assert!(details.sections.iter().all(|s| s.entries.is_ok()));
+    }
+}
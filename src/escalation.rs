@@ -0,0 +1,243 @@
+//! Escalating kill: SIGTERM first, then SIGKILL for processes that outlive the grace period.
+
+use crate::signal::{KillSignal, ProcessHandle, SignalOutcome};
+use rustix::process::Signal;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+pub const MAX_GRACE_PERIOD: Duration = Duration::from_secs(60);
+/// How long to wait for processes to exit after SIGKILL, before reporting them as still alive.
+/// Processes normally die right away, unless stuck in uninterruptible sleep.
+const KILL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Grace period changed by whole seconds, kept between 1s and [`MAX_GRACE_PERIOD`].
+pub fn adjust_grace_period(grace_period: Duration, delta_secs: i64) -> Duration {
+    let secs = grace_period.as_secs() as i64 + delta_secs;
+    Duration::from_secs(secs.max(1) as u64).min(MAX_GRACE_PERIOD)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscalationStatus {
+    /// SIGTERM sent, waiting for the process to exit.
+    Signalled,
+    Exited,
+    /// SIGKILL sent, waiting for the process to exit.
+    Escalated,
+    /// Survived SIGKILL, or couldn't be signalled at all.
+    StillAlive,
+}
+
+impl EscalationStatus {
+    fn is_pending(self) -> bool {
+        matches!(self, Self::Signalled | Self::Escalated)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EscalationEntry {
+    pub process: ProcessHandle,
+    pub status: EscalationStatus,
+    /// Why the process couldn't be signalled.
+    pub error: Option<SignalOutcome>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Escalation {
+    pub entries: Vec<EscalationEntry>,
+    pub started: Instant,
+    pub grace_period: Duration,
+    /// When SIGKILL was sent to the survivors.
+    pub escalated: Option<Instant>,
+}
+
+impl Escalation {
+    /// Sends SIGTERM to all given processes.
+    pub fn start(processes: Vec<ProcessHandle>, grace_period: Duration) -> Self {
+        let entries = processes
+            .into_iter()
+            .map(|process| {
+                let mut entry = EscalationEntry {
+                    process,
+                    status: EscalationStatus::Signalled,
+                    error: None,
+                };
+                entry.send(Signal::Term);
+                entry
+            })
+            .collect();
+        Self {
+            entries,
+            started: Instant::now(),
+            grace_period,
+            escalated: None,
+        }
+    }
+
+    /// Checks which processes have exited and sends SIGKILL once the grace period is over.
+    pub fn tick(&mut self, now: Instant) {
+        for entry in &mut self.entries {
+            if entry.status.is_pending() && !entry.process.key.is_running() {
+                entry.status = EscalationStatus::Exited;
+            }
+        }
+
+        match self.escalated {
+            None if now >= self.started + self.grace_period => {
+                self.escalated = Some(now);
+                for entry in &mut self.entries {
+                    if entry.status == EscalationStatus::Signalled {
+                        entry.status = EscalationStatus::Escalated;
+                        entry.send(Signal::Kill);
+                    }
+                }
+            }
+            Some(escalated) if now >= escalated + KILL_TIMEOUT => {
+                for entry in &mut self.entries {
+                    if entry.status == EscalationStatus::Escalated {
+                        entry.status = EscalationStatus::StillAlive;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// No process is waited on anymore.
+    pub fn is_finished(&self) -> bool {
+        !self.entries.iter().any(|entry| entry.status.is_pending())
+    }
+
+    /// Time left until SIGKILL is sent, if it hasn't been sent yet.
+    pub fn time_left(&self, now: Instant) -> Option<Duration> {
+        match self.escalated {
+            None => Some((self.started + self.grace_period).saturating_duration_since(now)),
+            Some(_) => None,
+        }
+    }
+
+    pub fn count(&self, status: EscalationStatus) -> usize {
+        self.entries.iter().filter(|e| e.status == status).count()
+    }
+}
+
+impl EscalationEntry {
+    fn send(&mut self, sig: Signal) {
+        match self.process.send(KillSignal::Named(sig)) {
+            SignalOutcome::Sent => {}
+            SignalOutcome::NoSuchProcess | SignalOutcome::AlreadyGone => {
+                self.status = EscalationStatus::Exited
+            }
+            outcome => {
+                self.status = EscalationStatus::StillAlive;
+                self.error = Some(outcome);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_data::ProcessKey;
+    use std::process::{Child, Command};
+
+    fn handle(child: &Child) -> ProcessHandle {
+        let pid = child.id() as i32;
+        let stat = procfs::process::Process::new(pid).unwrap().stat().unwrap();
+        let key = ProcessKey {
+            pid,
+            start_time: stat.starttime,
+        };
+        ProcessHandle::open(key, "sleep".to_string())
+    }
+
+    /// Stopped processes keep SIGTERM pending, so they outlive the grace period.
+    fn spawn_stopped() -> (Child, ProcessHandle) {
+        let child = Command::new("sleep").arg("60").spawn().unwrap();
+        let process = handle(&child);
+        assert!(process.send(KillSignal::Named(Signal::Stop)).is_sent());
+        wait_until("stopped", || process.key.is_stopped());
+        (child, process)
+    }
+
+    /// Fails instead of hanging, if `condition` doesn't become true.
+    fn wait_until(what: &str, mut condition: impl FnMut() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "timed out waiting until {what}");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_adjust_grace_period() {
+        let secs = Duration::from_secs;
+        assert_eq!(adjust_grace_period(DEFAULT_GRACE_PERIOD, 1), secs(6));
+        assert_eq!(adjust_grace_period(DEFAULT_GRACE_PERIOD, -1), secs(4));
+        assert_eq!(adjust_grace_period(secs(1), -1), secs(1));
+        assert_eq!(adjust_grace_period(MAX_GRACE_PERIOD, 1), MAX_GRACE_PERIOD);
+    }
+
+    #[test]
+    fn test_escalate_after_deadline() {
+        let (mut child, process) = spawn_stopped();
+        let mut escalation = Escalation::start(vec![process], DEFAULT_GRACE_PERIOD);
+        let started = escalation.started;
+
+        escalation.tick(started + Duration::from_secs(1));
+        assert_eq!(escalation.entries[0].status, EscalationStatus::Signalled);
+        assert_eq!(
+            escalation.time_left(started + Duration::from_secs(1)),
+            Some(Duration::from_secs(4))
+        );
+
+        escalation.tick(started + DEFAULT_GRACE_PERIOD);
+        assert_eq!(escalation.entries[0].status, EscalationStatus::Escalated);
+        assert_eq!(escalation.time_left(started + DEFAULT_GRACE_PERIOD), None);
+
+        // SIGKILL works on stopped processes too.
+        wait_until("finished", || {
+            escalation.tick(started + DEFAULT_GRACE_PERIOD);
+            escalation.is_finished()
+        });
+        assert_eq!(escalation.entries[0].status, EscalationStatus::Exited);
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_already_exited() {
+        let mut child = Command::new("true").spawn().unwrap();
+        let process = handle(&child);
+        child.wait().unwrap();
+
+        let escalation = Escalation::start(vec![process], DEFAULT_GRACE_PERIOD);
+        assert_eq!(escalation.entries[0].status, EscalationStatus::Exited);
+        assert!(escalation.is_finished());
+    }
+
+    #[test]
+    fn test_still_alive_after_kill_timeout() {
+        let (mut child, process) = spawn_stopped();
+        let now = Instant::now();
+        // As if SIGKILL had been sent, but didn't reach the process.
+        let mut escalation = Escalation {
+            entries: vec![EscalationEntry {
+                process,
+                status: EscalationStatus::Escalated,
+                error: None,
+            }],
+            started: now,
+            grace_period: Duration::ZERO,
+            escalated: Some(now),
+        };
+
+        escalation.tick(now + KILL_TIMEOUT / 2);
+        assert_eq!(escalation.entries[0].status, EscalationStatus::Escalated);
+        escalation.tick(now + KILL_TIMEOUT);
+        assert_eq!(escalation.entries[0].status, EscalationStatus::StillAlive);
+        assert!(escalation.is_finished());
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+}
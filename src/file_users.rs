@@ -0,0 +1,175 @@
+//! Finding processes that keep a file, directory tree or mount point busy,
+//! like `fuser -m` and `lsof +D`.
+
+use procfs::process::{FDTarget, MMapPath, Process};
+use std::collections::HashMap;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+/// Path the user asks about, as in `file:/mnt/usb`.
+#[derive(Debug)]
+pub struct PathQuery {
+    pub path: PathBuf,
+    /// Device of the filesystem, if `path` is a mount point. Then anything on that filesystem
+    /// matches, which is what keeps it from being unmounted.
+    pub mount_dev: Option<u64>,
+}
+
+impl PathQuery {
+    pub fn new(path: &str) -> Self {
+        let path = match (path.strip_prefix('~'), std::env::var_os("HOME")) {
+            (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+                PathBuf::from(format!("{}{rest}", home.to_string_lossy()))
+            }
+            _ => PathBuf::from(path),
+        };
+        // Symlinks are resolved in /proc too. Paths that don't exist just won't match.
+        let path = path.canonicalize().unwrap_or(path);
+        let mount_dev = mount_dev(&path);
+        Self { path, mount_dev }
+    }
+
+    /// `exact` only matches the path itself, not anything inside of it.
+    pub fn matches(&self, used: &UsedPath, exact: bool) -> bool {
+        match (self.mount_dev, used.dev) {
+            (Some(mount_dev), Some(dev)) if !exact => mount_dev == dev,
+            _ if exact => used.path == self.path,
+            _ => used.path.starts_with(&self.path),
+        }
+    }
+}
+
+fn mount_dev(path: &Path) -> Option<u64> {
+    let dev = std::fs::metadata(path).ok()?.dev();
+    let is_mount_point = match path.parent() {
+        Some(parent) => std::fs::metadata(parent).ok()?.dev() != dev,
+        None => true, // root
+    };
+    is_mount_point.then_some(dev)
+}
+
+/// File, directory or mapping used by a process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsedPath {
+    pub path: PathBuf,
+    /// Device the file lives on, read only when searching for a mount point.
+    pub dev: Option<u64>,
+}
+
+/// Paths used by each process: open files, working and root directories, executables and
+/// memory mapped files (e.g. shared libraries).
+#[derive(Debug, Default)]
+pub struct FileUsers {
+    paths: HashMap<i32, Vec<UsedPath>>,
+}
+
+impl FileUsers {
+    /// Reads paths of all processes. Processes of other users are only visible with elevated
+    /// privileges. `with_devices` is slow, as it has to `stat` every open file.
+    pub fn read(with_devices: bool) -> Self {
+        let paths = procfs::process::all_processes()
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|ps| (ps.pid(), used_paths(&ps, with_devices)))
+            .filter(|(_, paths)| !paths.is_empty())
+            .collect();
+        Self { paths }
+    }
+
+    pub fn paths(&self, pid: i32) -> impl Iterator<Item = &UsedPath> {
+        self.paths.get(&pid).into_iter().flatten()
+    }
+}
+
+fn used_paths(ps: &Process, with_devices: bool) -> Vec<UsedPath> {
+    let proc_dir = PathBuf::from(format!("/proc/{}", ps.pid()));
+    // stat() on /proc links reaches the file itself, even if it's been deleted or is in
+    // another mount namespace.
+    let dev = |link: &str| {
+        with_devices
+            .then(|| std::fs::metadata(proc_dir.join(link)).ok())
+            .flatten()
+            .map(|meta| meta.dev())
+    };
+
+    let mut paths = vec![];
+    let links = [("cwd", ps.cwd()), ("root", ps.root()), ("exe", ps.exe())];
+    for (link, path) in links {
+        if let Ok(path) = path {
+            paths.push(UsedPath {
+                path,
+                dev: dev(link),
+            });
+        }
+    }
+
+    for fd in ps.fd().into_iter().flatten().flatten() {
+        if let FDTarget::Path(path) = fd.target {
+            paths.push(UsedPath {
+                path,
+                dev: dev(&format!("fd/{}", fd.fd)),
+            });
+        }
+    }
+
+    for map in ps.maps().map(|maps| maps.0).unwrap_or_default() {
+        if let MMapPath::Path(path) = map.pathname {
+            let (major, minor) = map.dev;
+            let dev = with_devices.then(|| libc::makedev(major as u32, minor as u32));
+            paths.push(UsedPath { path, dev });
+        }
+    }
+
+    // Libraries are mapped many times.
+    paths.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+    paths.dedup();
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_query_matches() {
+        let query = PathQuery {
+            path: PathBuf::from("/home/user/project"),
+            mount_dev: None,
+        };
+        let used = |path: &str| UsedPath {
+            path: PathBuf::from(path),
+            dev: None,
+        };
+        assert!(query.matches(&used("/home/user/project"), false));
+        assert!(query.matches(&used("/home/user/project/src/main.rs"), false));
+        assert!(!query.matches(&used("/home/user/project2"), false));
+        assert!(!query.matches(&used("/home/user/project/src/main.rs"), true));
+
+        let mount = PathQuery {
+            path: PathBuf::from("/mnt/usb"),
+            mount_dev: Some(42),
+        };
+        let on_dev = |dev| UsedPath {
+            path: PathBuf::from("/elsewhere/file"),
+            dev: Some(dev),
+        };
+        assert!(mount.matches(&on_dev(42), false));
+        assert!(!mount.matches(&on_dev(7), false));
+    }
+
+    #[test]
+    fn test_find_own_open_file() {
+        let dir = std::env::temp_dir().join(format!("killa-file-users-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = std::fs::File::create(dir.join("busy")).unwrap();
+
+        let users = FileUsers::read(false);
+        let query = PathQuery::new(dir.to_str().unwrap());
+        let pid = std::process::id() as i32;
+        assert!(users.paths(pid).any(|used| query.matches(used, false)));
+
+        drop(file);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,5 +1,6 @@
-use crate::ui::{ColumnKind, Message, TextInputAction};
+use crate::ui::{ColumnKind, Message, SelectionMove, TextInputAction};
 use iced::{Event, Subscription, event, keyboard};
+use rustix::process::Signal;
 
 pub fn handle_keybinds() -> Subscription<Message> {
     event::listen_with(|event, status, _window| -> Option<Message> {
@@ -10,15 +11,19 @@ pub fn handle_keybinds() -> Subscription<Message> {
         // Right now, there's only one widget that can capture input - seach box.
         let is_search_box_active = matches!(status, event::Status::Captured);
 
-        let (key, modifiers) =
-            if let Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) = event {
-                (key, modifiers)
-            } else {
-                return None;
-            };
+        let (key, modifiers) = match event {
+            Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => (key, modifiers),
+            // Tracked for Ctrl+click.
+            Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                return Some(Message::ModifiersChanged(modifiers));
+            }
+            _ => return None,
+        };
 
         const NO_MODS: iced::keyboard::Modifiers = M::empty();
         const CTRL_SHIFT: iced::keyboard::Modifiers = M::CTRL.union(M::SHIFT);
+        const CTRL_ALT: iced::keyboard::Modifiers = M::CTRL.union(M::ALT);
+        const CTRL_ALT_SHIFT: iced::keyboard::Modifiers = CTRL_ALT.union(M::SHIFT);
 
         // keybinds
         let res: Option<_> = match (modifiers, key.as_ref(), is_search_box_active) {
@@ -46,14 +51,61 @@ pub fn handle_keybinds() -> Subscription<Message> {
             (M::CTRL, T::Character("1"), _) => Some(Message::SetSortField(ColumnKind::Cpu)),
             (M::CTRL, T::Character("2"), _) => Some(Message::SetSortField(ColumnKind::Memory)),
             (M::CTRL, T::Character("3"), _) => Some(Message::SetSortField(ColumnKind::Pid)),
+            (M::CTRL, T::Character("s"), _) => Some(Message::ReverseSortOrder),
+
+            (M::CTRL, T::Character("t"), _) => Some(Message::ToggleTreeMode),
+
+            ////////////////////////
+            // row selection
+            (NO_MODS, T::Named(K::ArrowUp), _) => Some(Message::MoveSelection(SelectionMove::Up)),
+            (NO_MODS, T::Named(K::ArrowDown), _) => {
+                Some(Message::MoveSelection(SelectionMove::Down))
+            }
+            (NO_MODS, T::Named(K::PageUp), _) => {
+                Some(Message::MoveSelection(SelectionMove::PageUp))
+            }
+            (NO_MODS, T::Named(K::PageDown), _) => {
+                Some(Message::MoveSelection(SelectionMove::PageDown))
+            }
+            // Home/End move the text cursor in the search box.
+            (NO_MODS, T::Named(K::Home), false) => {
+                Some(Message::MoveSelection(SelectionMove::Home))
+            }
+            (NO_MODS, T::Named(K::End), false) => Some(Message::MoveSelection(SelectionMove::End)),
+            (NO_MODS, T::Named(K::Space), false) => Some(Message::ToggleMarkSelected),
+            (M::CTRL, T::Named(K::Space), _) => Some(Message::ToggleMarkSelected),
 
             // other
-            (M::CTRL, T::Character("k"), _) => Some(Message::StageSignalAllFiltered(
-                rustix::process::Signal::Term,
-            )),
-            (CTRL_SHIFT, T::Character("k"), _) => Some(Message::StageSignalAllFiltered(
-                rustix::process::Signal::Kill,
-            )),
+            (M::CTRL, T::Character("k"), _) => Some(Message::StageSignal(Signal::Term.into())),
+            (CTRL_SHIFT, T::Character("k"), _) => Some(Message::StageSignal(Signal::Kill.into())),
+
+            (CTRL_ALT, T::Character("k"), _) => {
+                Some(Message::StageSignalSubtree(Signal::Term.into()))
+            }
+            (CTRL_ALT_SHIFT, T::Character("k"), _) => {
+                Some(Message::StageSignalSubtree(Signal::Kill.into()))
+            }
+            (M::CTRL, T::Character("p"), _) => Some(Message::OpenSignalPicker),
+            (M::CTRL, T::Character("z"), _) => Some(Message::StageSignal(Signal::Stop.into())),
+            (CTRL_SHIFT, T::Character("z"), _) => Some(Message::StageSignal(Signal::Cont.into())),
+            (CTRL_ALT, T::Character("z"), _) => Some(Message::ResumeAllPaused),
+            (M::CTRL, T::Character("r"), _) => Some(Message::OpenPriorityDialog),
+            (CTRL_SHIFT, T::Character("a"), _) => Some(Message::OpenAffinityDialog),
+            (M::CTRL, T::Character("u"), _) => Some(Message::OpenPathUsersDialog),
+            (M::CTRL, T::Character("o"), _) => Some(Message::OpenColumnChooser),
+            (NO_MODS, T::Named(K::F5), _) => Some(Message::RefreshDetails),
+            (M::CTRL, T::Character("g"), _) => {
+                Some(Message::StageSignalCgroup(Signal::Kill.into()))
+            }
+            (CTRL_SHIFT, T::Character("g"), _) => {
+                Some(Message::StageSignalCgroup(Signal::Stop.into()))
+            }
+            (CTRL_ALT, T::Character("g"), _) => {
+                Some(Message::StageSignalCgroup(Signal::Cont.into()))
+            }
+            (M::CTRL, T::Character("e"), _) => Some(Message::StageEscalatingKill),
+            (M::CTRL, T::Character("="), _) => Some(Message::ChangeGracePeriod(1)),
+            (M::CTRL, T::Character("-"), _) => Some(Message::ChangeGracePeriod(-1)),
 
             _ => None,
         };
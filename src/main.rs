@@ -1,22 +1,40 @@
 use crate::collector::colv2::run_collector_worker;
-use crate::ui::{ColumnKind, Message, Row, TextInputAction};
+use crate::ui::{Column, ColumnKind, Message, Row, SelectionMove, TextInputAction};
+use affinity::CpuList;
+use cgroup::CgroupFs;
 use collector::init::init_collector;
+use details::ProcessDetails;
+use escalation::{Escalation, EscalationStatus};
 use iced::alignment::{Horizontal, Vertical};
 use iced::widget::container::background;
 use iced::widget::{
-    self, checkbox, column, container, responsive, row, scrollable, text, text_input,
+    self, button, center, checkbox, column, container, mouse_area, opaque, pick_list, responsive,
+    row, scrollable, slider, stack, text, text_input,
 };
 use iced::window::{self};
 use iced::{
     Color, Element, Font, Length, Renderer, Size, Subscription, Task, Theme, border, color,
+    keyboard,
 };
-use process_data::{KillaData, ProcessListSort, SortOrder};
-use rustix::process::{Signal, kill_process};
+use priority::{IoClass, IoPriority};
+use process_data::{KillaData, ProcessKey, ProcessListSort, SearchQuery, SortOrder};
+use rustix::process::Signal;
+use signal::{ActionResult, ActionResults, KillSignal, ProcessHandle, SignalOutcome};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
 
-mod collect_uptimes;
+mod affinity;
+mod cgroup;
 mod collector;
+mod config;
+mod details;
+mod escalation;
+mod file_users;
 mod keybinds;
+mod priority;
 mod process_data;
+mod signal;
+mod sockets;
 mod ui;
 
 fn main() {
@@ -41,11 +59,27 @@ fn main() {
 }
 
 const SEARCH_INPUT_ID: iced::widget::Id = iced::widget::Id::new("global-search");
+const SIGNAL_PICKER_ID: iced::widget::Id = iced::widget::Id::new("signal-picker");
+const AFFINITY_INPUT_ID: iced::widget::Id = iced::widget::Id::new("affinity-input");
+const PATH_USERS_INPUT_ID: iced::widget::Id = iced::widget::Id::new("path-users-input");
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct SearchState {
     pub is_hidden: bool,
     pub text: String,
+    /// Parsed [`Self::text`], see [`Self::refresh_query`].
+    pub query: SearchQuery,
+    query_text: String,
+}
+
+impl SearchState {
+    /// Re-parses the query, if the text has changed since the last call.
+    pub fn refresh_query(&mut self) {
+        if self.query_text != self.text {
+            self.query = SearchQuery::parse(&self.text);
+            self.query_text.clone_from(&self.text);
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -55,7 +89,7 @@ pub enum FreezeState {
 }
 
 struct App {
-    pub columns: Vec<ColumnKind>,
+    pub columns: Vec<Column>,
     pub rows: Vec<Row>,
     pub table_top_id: widget::Id,
     pub theme: Theme,
@@ -63,8 +97,99 @@ struct App {
     pub sort: ProcessListSort,
     pub last_data: KillaData,
     pub freeze: FreezeState,
-    pub staged_sig_all_filtered: Option<rustix::process::Signal>,
+    pub staged_signal: Option<StagedSignal>,
     pub wireframe_enabled: bool,
+    pub tree: TreeState,
+    /// Row highlighted with the keyboard cursor, kept across refreshes.
+    pub selected_pid: Option<i32>,
+    /// Processes marked for signalling with Space or Ctrl+click.
+    pub marked: HashSet<ProcessKey>,
+    pub modifiers: keyboard::Modifiers,
+    pub signal_picker: Option<SignalPicker>,
+    /// Signal chosen the last time the signal picker was used.
+    pub last_picked_signal: KillSignal,
+    /// Progress of the last escalating kill, shown until dismissed with Esc.
+    pub escalation: Option<Escalation>,
+    /// Time given to processes to exit after SIGTERM, before escalating to SIGKILL.
+    pub grace_period: Duration,
+    /// Results of the last signal sent with Enter, shown until dismissed with Esc.
+    pub action_results: Option<ActionResults>,
+    /// Processes paused with SIGSTOP in this session, until they're continued.
+    pub paused: HashSet<ProcessKey>,
+    pub priority_dialog: Option<PriorityDialog>,
+    pub affinity_dialog: Option<AffinityDialog>,
+    /// "Who is using this path" dialog, with the path typed so far.
+    pub path_users_dialog: Option<String>,
+    pub column_chooser: Option<ColumnChooser>,
+    pub cgroupfs: CgroupFs,
+    /// `/proc` details of the selected process, shown in the side panel.
+    pub details: Option<ProcessDetails>,
+}
+
+/// Signal waiting for confirmation with Enter.
+#[derive(Clone, Debug)]
+pub struct StagedSignal {
+    pub signal: KillSignal,
+    pub target: SignalTarget,
+    /// Target processes, opened at staging time so that reused pids aren't signalled.
+    pub processes: Vec<ProcessHandle>,
+    /// Send SIGKILL to processes still alive after the grace period.
+    pub escalate: bool,
+}
+
+/// Dialog for choosing a signal other than SIGTERM/SIGKILL.
+#[derive(Clone, Debug)]
+pub struct SignalPicker {
+    pub signals: Vec<KillSignal>,
+    /// Index of the highlighted signal.
+    pub cursor: usize,
+    pub target: SignalTarget,
+}
+
+#[derive(Clone, Debug)]
+pub enum SignalTarget {
+    AllFiltered,
+    Selected(i32),
+    Marked,
+    /// Process and all of its descendants.
+    Subtree(i32),
+    /// All processes in a cgroup v2 group, see [`CgroupFs::process_cgroup`].
+    Cgroup(String),
+}
+
+/// Dialog for changing nice value and I/O priority.
+#[derive(Clone, Debug)]
+pub struct PriorityDialog {
+    pub target: SignalTarget,
+    pub processes: Vec<ProcessHandle>,
+    pub nice: i32,
+    pub io_priority: IoPriority,
+    /// Only edited values are applied, so that others aren't overwritten with the values of the
+    /// first process.
+    pub nice_changed: bool,
+    pub io_priority_changed: bool,
+}
+
+/// Dialog for changing CPU affinity.
+#[derive(Clone, Debug)]
+pub struct AffinityDialog {
+    pub target: SignalTarget,
+    pub processes: Vec<ProcessHandle>,
+    /// CPU list as typed by the user, see [`CpuList::parse`].
+    pub text: String,
+    pub all_threads: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct ColumnChooser {
+    /// Error from the last attempt to save the columns.
+    pub save_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct TreeState {
+    pub enabled: bool,
+    pub collapsed_pids: HashSet<i32>,
 }
 
 impl App {
@@ -78,13 +203,25 @@ impl App {
                 }
                 collector::colv2::Event::WorkFinished => Task::none(),
             });
-        (Self::default(), Task::batch(vec![init_collector_task]))
+        let mut app = Self::default();
+        if let Some(columns) = config::load_columns() {
+            app.columns = columns;
+        }
+        (app, Task::batch(vec![init_collector_task]))
     }
 
-    fn subscription(_: &Self) -> Subscription<Message> {
+    fn subscription(&self) -> Subscription<Message> {
+        let escalation_ticks = match &self.escalation {
+            Some(escalation) if !escalation.is_finished() => {
+                iced::time::every(Duration::from_millis(200)).map(Message::EscalationTick)
+            }
+            _ => Subscription::none(),
+        };
+
         Subscription::batch([
             iced::system::theme_changes().map(Message::SystemThemeChanged),
             keybinds::handle_keybinds(),
+            escalation_ticks,
         ])
     }
 
@@ -93,13 +230,42 @@ impl App {
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
+        // Dialogs handle user input, while data collection and such continue in the background.
+        let is_background = matches!(
+            message,
+            Message::CollectedData(_)
+                | Message::DetailsLoaded(_)
+                | Message::LoadDetails(_)
+                | Message::EscalationTick(_)
+                | Message::ModifiersChanged(_)
+                | Message::SystemThemeChanged(_)
+        );
+        if !is_background {
+            if self.signal_picker.is_some() {
+                return self.update_signal_picker(message);
+            }
+            if self.priority_dialog.is_some() {
+                return self.update_priority_dialog(message);
+            }
+            if self.affinity_dialog.is_some() {
+                return self.update_affinity_dialog(message);
+            }
+            if self.path_users_dialog.is_some() {
+                return self.update_path_users_dialog(message);
+            }
+            if self.column_chooser.is_some() {
+                return self.update_column_chooser(message);
+            }
+        }
+
         match message {
             Message::Search(ev) => {
-                self.staged_sig_all_filtered = None;
+                self.staged_signal = None;
                 return self.handle_search(ev);
             }
             Message::CollectedData(data) => {
-                let kd = KillaData::from(data);
+                let mut kd = KillaData::from(data);
+                kd.read_columns(&self.columns);
                 if let FreezeState::Enabled(d) = &mut self.freeze {
                     d.replace(kd);
                     return Task::none();
@@ -108,15 +274,30 @@ impl App {
                 self.sort_rows();
                 self.filter_rows();
             }
+            Message::RefreshDetails => {
+                if let Some(pid) = self.selected_pid {
+                    return read_details(pid);
+                }
+            }
+            Message::LoadDetails(pid) => {
+                if self.selected_pid == Some(pid) && !self.has_details_of(pid) {
+                    return read_details(pid);
+                }
+            }
+            Message::DetailsLoaded(details) => {
+                if self.selected_pid == Some(details.pid) {
+                    self.details = Some(details);
+                }
+            }
             Message::Freeze(enable) => {
-                self.staged_sig_all_filtered = None;
+                self.staged_signal = None;
                 self.set_freeze(false);
                 self.sort_rows();
                 self.filter_rows();
                 self.set_freeze(enable);
             }
             Message::ToggleFreeze => {
-                self.staged_sig_all_filtered = None;
+                self.staged_signal = None;
                 if matches!(self.freeze, FreezeState::Enabled(_)) {
                     self.set_freeze(false);
                     self.sort_rows();
@@ -127,34 +308,227 @@ impl App {
             }
             Message::ToggleWireframe(enabled) => self.wireframe_enabled = enabled,
             Message::SetSortField(sort_field) => {
-                self.staged_sig_all_filtered = None;
-                self.sort.column = sort_field;
+                self.staged_signal = None;
+                self.sort.select(sort_field, self.modifiers.shift());
+                self.sort_rows();
+                self.filter_rows();
+            }
+            Message::ReverseSortOrder => {
+                self.staged_signal = None;
+                self.sort.order = self.sort.order.reversed();
                 self.sort_rows();
                 self.filter_rows();
             }
+            Message::ToggleTreeMode => {
+                self.staged_signal = None;
+                self.tree.enabled = !self.tree.enabled;
+                self.filter_rows();
+            }
+            Message::ToggleCollapsed(pid) => {
+                self.staged_signal = None;
+                if !self.tree.collapsed_pids.remove(&pid) {
+                    self.tree.collapsed_pids.insert(pid);
+                }
+                self.filter_rows();
+            }
+            Message::MoveSelection(movement) => {
+                self.staged_signal = None;
+                return self.move_selection(movement);
+            }
+            Message::Select(pid) => {
+                self.staged_signal = None;
+                if self.modifiers.control() {
+                    self.toggle_mark(pid);
+                } else {
+                    self.selected_pid = Some(pid);
+                }
+                self.filter_rows();
+                return self.load_details_later();
+            }
+            Message::ToggleMarkSelected => {
+                if let Some(pid) = self.selected_pid {
+                    self.staged_signal = None;
+                    self.toggle_mark(pid);
+                    self.filter_rows();
+                }
+            }
+            Message::ModifiersChanged(modifiers) => self.modifiers = modifiers,
             Message::Back => {
-                if self.staged_sig_all_filtered.is_some() {
-                    self.staged_sig_all_filtered = None;
+                // The confirmation is shown over the panels, so it's cancelled first.
+                let cancelled = self.staged_signal.take().is_some();
+                // Dismissing an unfinished escalation cancels the pending SIGKILL.
+                if !cancelled
+                    && (self.escalation.take().is_some() || self.action_results.take().is_some())
+                {
+                    return Task::none();
                 }
 
                 if matches!(self.freeze, FreezeState::Enabled(_)) {
                     self.set_freeze(false);
                     self.sort_rows();
                     self.filter_rows();
+                } else if self.selected_pid.is_some() {
+                    self.selected_pid = None;
+                    self.filter_rows();
+                } else if !self.marked.is_empty() {
+                    self.marked.clear();
+                    self.filter_rows();
                 } else {
                     return self.handle_search(TextInputAction::Hide);
                 }
             }
             Message::Enter => {
-                if let Some(sig) = self.staged_sig_all_filtered {
-                    self.staged_sig_all_filtered = None;
-                    self.sig_all_filtered(sig);
+                if let Some(staged) = self.staged_signal.take() {
+                    match &staged.target {
+                        _ if staged.escalate => self.start_escalation(staged.processes),
+                        SignalTarget::Subtree(_) => {
+                            self.send_signal_subtree(staged.signal, staged.processes)
+                        }
+                        SignalTarget::Cgroup(cgroup) => {
+                            self.apply_to_cgroup(staged.signal, cgroup, staged.processes)
+                        }
+                        _ => self.send_signal(staged.signal, staged.processes),
+                    }
                     self.set_freeze(false);
                 }
             }
-            Message::StageSignalAllFiltered(sig) => {
-                if matches!(self.freeze, FreezeState::Enabled(_)) && self.search.text.len() >= 3 {
-                    self.staged_sig_all_filtered = Some(sig);
+            Message::StageSignal(signal) => {
+                if let Some(target) = self.signal_target() {
+                    self.stage_signal(signal, target, false);
+                }
+            }
+            Message::StageEscalatingKill => {
+                // Starting another escalation would drop the pending SIGKILL of this one.
+                if self.escalation.as_ref().is_some_and(|e| !e.is_finished()) {
+                    return Task::none();
+                }
+                if let Some(target) = self.signal_target() {
+                    self.stage_signal(Signal::Term.into(), target, true);
+                }
+            }
+            Message::ResumeAllPaused => {
+                // Exited processes can't be resumed anymore.
+                self.paused.retain(ProcessKey::is_same_process);
+                if self.paused.is_empty() {
+                    return Task::none();
+                }
+                let processes = self
+                    .paused
+                    .iter()
+                    .map(|key| {
+                        let name = match self.last_data.get(key.pid) {
+                            Some(ps) => ps.name.clone(),
+                            None => String::new(),
+                        };
+                        ProcessHandle::open(*key, name)
+                    })
+                    .collect();
+                self.send_signal(Signal::Cont.into(), processes);
+                self.set_freeze(false);
+            }
+            Message::ChangeGracePeriod(delta_secs) => {
+                self.grace_period = escalation::adjust_grace_period(self.grace_period, delta_secs);
+            }
+            Message::EscalationTick(now) => {
+                if let Some(escalation) = &mut self.escalation {
+                    escalation.tick(now);
+                }
+            }
+            Message::OpenSignalPicker => {
+                if let Some(target) = self.signal_target() {
+                    self.set_freeze(true);
+                    self.staged_signal = None;
+                    let signals = KillSignal::all();
+                    let cursor = signals
+                        .iter()
+                        .position(|sig| *sig == self.last_picked_signal)
+                        .unwrap_or_default();
+                    self.signal_picker = Some(SignalPicker {
+                        signals,
+                        cursor,
+                        target,
+                    });
+                    return self.scroll_signal_picker();
+                }
+            }
+            Message::OpenPriorityDialog => {
+                if let Some(target) = self.signal_target() {
+                    self.set_freeze(true);
+                    self.staged_signal = None;
+                    let processes = self.target_processes(&target);
+                    // Start with the current values of the first process.
+                    let first = processes.first().map(|process| process.key.pid);
+                    self.priority_dialog = Some(PriorityDialog {
+                        target,
+                        nice: first
+                            .and_then(|pid| self.last_data.get(pid))
+                            .map(|ps| ps.nice)
+                            .unwrap_or_default(),
+                        io_priority: first
+                            .and_then(|pid| IoPriority::get(pid).ok())
+                            .unwrap_or_default(),
+                        nice_changed: false,
+                        io_priority_changed: false,
+                        processes,
+                    });
+                }
+            }
+            Message::OpenAffinityDialog => {
+                if let Some(target) = self.signal_target() {
+                    self.set_freeze(true);
+                    self.staged_signal = None;
+                    let processes = self.target_processes(&target);
+                    // Start with the current affinity of the first process.
+                    let text = processes
+                        .first()
+                        .and_then(|process| CpuList::get(process.key.pid).ok())
+                        .map(|cpus| cpus.to_string())
+                        .unwrap_or_default();
+                    self.affinity_dialog = Some(AffinityDialog {
+                        target,
+                        processes,
+                        text,
+                        all_threads: true,
+                    });
+                    return Task::batch([
+                        widget::operation::focus(AFFINITY_INPUT_ID),
+                        widget::operation::select_all(AFFINITY_INPUT_ID),
+                    ]);
+                }
+            }
+            Message::OpenColumnChooser => {
+                self.staged_signal = None;
+                self.column_chooser = Some(ColumnChooser::default());
+            }
+            Message::OpenPathUsersDialog => {
+                self.staged_signal = None;
+                self.path_users_dialog = Some(String::new());
+                return widget::operation::focus(PATH_USERS_INPUT_ID);
+            }
+            // Only sent while the respective dialog is open.
+            Message::PickSignal(_)
+            | Message::SetNice(_)
+            | Message::SetIoClass(_)
+            | Message::SetIoLevel(_)
+            | Message::SetAffinityText(_)
+            | Message::SetAffinityAllThreads(_)
+            | Message::SetPathUsersText(_)
+            | Message::ToggleColumn(..)
+            | Message::MoveColumn(..)
+            | Message::SetColumnWidth(..)
+            | Message::SaveColumns
+            | Message::ResetColumns => {}
+            Message::StageSignalCgroup(signal) => match self.selected_cgroup(signal) {
+                Ok(cgroup) => self.stage_signal(signal, SignalTarget::Cgroup(cgroup), false),
+                Err(err) => self.show_group_result(
+                    format!("{} cgroup", cgroup_action(signal)),
+                    SignalOutcome::Other(err),
+                    vec![],
+                ),
+            },
+            Message::StageSignalSubtree(signal) => {
+                if let Some(pid) = self.selected_pid {
+                    self.stage_signal(signal, SignalTarget::Subtree(pid), false);
                 }
             }
             Message::SystemThemeChanged(mode) => {
@@ -172,16 +546,20 @@ impl App {
     /// This is called every time a Message has been processed in [`Self::update`]
     fn view(&self) -> iced::Element<'_, Message, Theme, Renderer> {
         let table = scrollable(responsive(|size| {
-            // TODO: Make columns resizable
+            // Scaled to fill the table, whichever columns are visible.
+            let total_width: f32 = self.columns.iter().map(|column| column.width).sum();
             let table: widget::table::Table<'_, Message> = widget::table::table(
                 self.columns
                     .iter()
-                    .map(|col_kind| {
-                        widget::table::column(
-                            widget::text(col_kind.to_string())
-                                .width(size.width * col_kind.width_ratio()),
-                            |row: Row| row.cell(col_kind),
-                        )
+                    .map(|column| {
+                        let title = format!("{}{}", column.kind, self.sort.indicator(column.kind));
+                        let header =
+                            widget::text(title).width(size.width * column.width / total_width);
+                        // Shift+click sorts by the column as secondary key.
+                        let header = mouse_area(header)
+                            .on_press(Message::SetSortField(column.kind))
+                            .on_right_press(Message::OpenColumnChooser);
+                        widget::table::column(header, |row: Row| row.cell(&column.kind))
                     })
                     .collect::<Vec<_>>(),
                 self.rows.clone(),
@@ -190,14 +568,27 @@ impl App {
         }))
         .id(self.table_top_id.clone());
 
-        let topbar_left = column![
+        let mut topbar_left = column![
             checkbox(matches!(self.freeze, FreezeState::Enabled(_))) // TODO: "Freeze" label
                 .on_toggle(|_| Message::ToggleFreeze),
             checkbox(self.wireframe_enabled).on_toggle(Message::ToggleWireframe), // TODO: "Wireframe" label.
-            text(format!("Sorting By {:?}", self.sort.column))
+            text(format!(
+                "Sorting by {}{}",
+                self.sort,
+                if self.tree.enabled { " (tree)" } else { "" }
+            ))
         ]
         .spacing(6);
 
+        if !self.marked.is_empty() {
+            topbar_left = topbar_left.push(text!("{} marked", self.marked.len()));
+        }
+        if !self.paused.is_empty() {
+            topbar_left = topbar_left.push(
+                text!("{} paused (Ctrl+Alt+Z: resume all)", self.paused.len()).style(text::warning),
+            );
+        }
+
         let total_memory_usage = {
             let used = (self.last_data.memory.used_bytes as f64) / 1_000_000_000.0;
             let total = (self.last_data.memory.total_bytes as f64) / 1_000_000_000.0;
@@ -211,10 +602,42 @@ impl App {
         let mut topbar = row![topbar_left].spacing(6);
 
         if self.search.is_hidden {
+            let errors = self.search.query.errors();
+            let has_errors = !errors.is_empty();
             let search_box = text_input("Search processes", &self.search.text)
                 .on_input(|text| Message::Search(TextInputAction::Replace(text)))
-                .id(SEARCH_INPUT_ID);
-            topbar = topbar.push(container(search_box).padding(10).align_y(Vertical::Center));
+                .id(SEARCH_INPUT_ID)
+                .style(move |theme: &Theme, status| {
+                    let style = text_input::default(theme, status);
+                    if has_errors {
+                        text_input::Style {
+                            border: style.border.color(theme.palette().danger).width(2),
+                            ..style
+                        }
+                    } else {
+                        style
+                    }
+                });
+
+            // Syntax errors are shown differently than no results, so they're not confused.
+            let hint = if let Some(err) = errors.first() {
+                let more = match errors.len() {
+                    1 => String::new(),
+                    n => format!(" (+{} more)", n - 1),
+                };
+                text!("Syntax error: {err}{more}").style(text::danger)
+            } else if self.rows.is_empty() && !self.search.text.trim().is_empty() {
+                text("No results").style(text::secondary)
+            } else {
+                text("")
+            }
+            .size(12);
+
+            topbar = topbar.push(
+                container(column![search_box, hint].spacing(2))
+                    .padding(10)
+                    .align_y(Vertical::Center),
+            );
         } else {
             topbar = topbar.push(container("").width(Length::Fill));
         }
@@ -238,11 +661,11 @@ impl App {
         let table = container(table).style(|theme| background(theme.palette().background));
 
         // red border on kill confirmation or cool blue on freeze.
-        let table = if let Some(sig) = self.staged_sig_all_filtered {
-            let color = match sig {
-                Signal::Term => color!(0xFF0000), // red
-                Signal::Kill => color!(0x9B26B6), // violet
-                _ => color!(0xCCFF00),            // yellow
+        let table = if let Some(StagedSignal { signal, .. }) = &self.staged_signal {
+            let color = match signal {
+                KillSignal::Named(Signal::Term) => color!(0xFF0000), // red
+                KillSignal::Named(Signal::Kill) => color!(0x9B26B6), // violet
+                _ => color!(0xCCFF00),                               // yellow
             };
             container(table).style(move |_theme| {
                 container::bordered_box(&self.theme).border(border::width(10).color(color))
@@ -257,7 +680,32 @@ impl App {
         }
         .padding(2);
 
-        let content = column![topbar, table,].spacing(6);
+        let mut content = column![topbar].spacing(6);
+        if let Some(escalation) = &self.escalation {
+            content = content.push(self.view_escalation(escalation));
+        }
+        if let Some(results) = &self.action_results {
+            content = content.push(self.view_action_results(results));
+        }
+        let content = match &self.details {
+            Some(details) => content.push(row![table, self.view_details(details)].spacing(6)),
+            None => content.push(table),
+        };
+        let content: Element<_> = if let Some(picker) = &self.signal_picker {
+            stack![content, self.view_signal_picker(picker)].into()
+        } else if let Some(dialog) = &self.priority_dialog {
+            stack![content, self.view_priority_dialog(dialog)].into()
+        } else if let Some(dialog) = &self.affinity_dialog {
+            stack![content, self.view_affinity_dialog(dialog)].into()
+        } else if let Some(path) = &self.path_users_dialog {
+            stack![content, self.view_path_users_dialog(path)].into()
+        } else if let Some(chooser) = &self.column_chooser {
+            stack![content, self.view_column_chooser(chooser)].into()
+        } else if let Some(staged) = &self.staged_signal {
+            stack![content, self.view_confirmation(staged)].into()
+        } else {
+            content.into()
+        };
 
         let all: Element<_> =
             container(container(content).width(Length::Fill).height(Length::Fill))
@@ -273,20 +721,544 @@ impl App {
         }
     }
 
+    fn view_escalation<'a>(&'a self, escalation: &'a Escalation) -> Element<'a, Message> {
+        let summary = match escalation.time_left(Instant::now()) {
+            _ if escalation.is_finished() => {
+                "Escalating kill finished (Esc to dismiss)".to_string()
+            }
+            Some(left) => format!(
+                "SIGKILL in {:.1}s (dismissing with Esc cancels it)",
+                left.as_secs_f32()
+            ),
+            None => "SIGKILL sent, waiting for processes to exit".to_string(),
+        };
+        let summary = format!(
+            "{summary}: {}/{} exited",
+            escalation.count(EscalationStatus::Exited),
+            escalation.entries.len()
+        );
+
+        let entries = column(escalation.entries.iter().map(|entry| {
+            let status = match entry.status {
+                EscalationStatus::Signalled => text("signalled"),
+                EscalationStatus::Exited => text("exited").style(text::success),
+                EscalationStatus::Escalated => text("escalated").style(text::danger),
+                EscalationStatus::StillAlive => match &entry.error {
+                    Some(outcome) => text!("still alive: {outcome}"),
+                    None => text("still alive"),
+                }
+                .style(text::danger),
+            };
+            row![
+                text!("{:>7}", entry.process.key.pid).font(Font::MONOSPACE),
+                text(&entry.process.name).width(Length::Fixed(200.0)),
+                status,
+            ]
+            .spacing(12)
+            .into()
+        }));
+
+        container(column![text(summary), scrollable(entries).height(Length::Shrink)].spacing(6))
+            .max_height(200)
+            .width(Length::Fill)
+            .padding(10)
+            .style(container::bordered_box)
+            .into()
+    }
+
+    fn view_action_results<'a>(&'a self, results: &'a ActionResults) -> Element<'a, Message> {
+        let outcome_text = |outcome: &SignalOutcome| {
+            let label = text(outcome.to_string());
+            match outcome {
+                SignalOutcome::Sent => label.style(text::success),
+                SignalOutcome::PermissionDenied => label.style(text::danger),
+                _ => label.style(text::warning),
+            }
+        };
+
+        let mut summary = match &results.group_outcome {
+            Some(outcome) => row![
+                text!("{}:", results.action),
+                outcome_text(outcome),
+                text!(
+                    "{} processes in group (Esc to dismiss)",
+                    results.results.len()
+                ),
+            ],
+            None => {
+                let sent = results.count(SignalOutcome::is_sent);
+                let failed = results.results.len() - sent;
+                row![text!(
+                    "{}: {sent} ok, {failed} failed (Esc to dismiss)",
+                    results.action
+                )]
+            }
+        }
+        .spacing(12);
+        let denied = match &results.group_outcome {
+            Some(outcome) => usize::from(*outcome == SignalOutcome::PermissionDenied),
+            None => results.count(|outcome| *outcome == SignalOutcome::PermissionDenied),
+        };
+        if denied > 0 {
+            summary = summary
+                .push(text!("{denied} denied, retry with elevated privileges").style(text::danger));
+        }
+
+        let entries = column(results.results.iter().map(|result| {
+            let mut entry = row![
+                text!("{:>7}", result.key.pid).font(Font::MONOSPACE),
+                text(&result.name).width(Length::Fixed(200.0)),
+            ]
+            .spacing(12);
+            // The group outcome is already in the summary.
+            if results.group_outcome.is_none() {
+                entry = entry.push(outcome_text(&result.outcome));
+            }
+            entry.into()
+        }));
+
+        container(column![summary, scrollable(entries).height(Length::Shrink)].spacing(6))
+            .max_height(200)
+            .width(Length::Fill)
+            .padding(10)
+            .style(container::bordered_box)
+            .into()
+    }
+
+    fn view_details<'a>(&'a self, details: &'a ProcessDetails) -> Element<'a, Message> {
+        const LABEL_WIDTH: f32 = 150.0;
+
+        // Details of the previous selection stay until the new ones are read, so that the table
+        // doesn't change width back and forth.
+        let is_loading = self.selected_pid != Some(details.pid);
+        let shown = if is_loading {
+            &[][..]
+        } else {
+            &details.sections[..]
+        };
+
+        let sections = column(shown.iter().map(|section| {
+            let entries: Element<_> = match &section.entries {
+                Ok(entries) if entries.is_empty() => text("-").size(12).into(),
+                Ok(entries) => column(entries.iter().map(|(label, value)| {
+                    let value = text(value).size(12).font(Font::MONOSPACE);
+                    if label.is_empty() {
+                        value.into()
+                    } else {
+                        row![
+                            text(label)
+                                .size(12)
+                                .style(text::secondary)
+                                .width(Length::Fixed(LABEL_WIDTH)),
+                            value,
+                        ]
+                        .into()
+                    }
+                }))
+                .spacing(2)
+                .into(),
+                Err(err) => text(err).size(12).style(text::danger).into(),
+            };
+            column![text(section.title).size(14), entries]
+                .spacing(4)
+                .into()
+        }))
+        .spacing(12);
+
+        let header = row![
+            if is_loading {
+                text("Loading...")
+            } else {
+                text!("As of {}", details.read_at.format("%H:%M:%S"))
+            }
+            .size(12)
+            .style(text::secondary)
+            .width(Length::Fill),
+            button(text("Refresh (F5)").size(12))
+                .style(button::secondary)
+                .on_press(Message::RefreshDetails),
+        ]
+        .align_y(Vertical::Center);
+
+        container(column![header, scrollable(sections).height(Length::Fill)].spacing(8))
+            .width(Length::Fixed(380.0))
+            .height(Length::Fill)
+            .padding(10)
+            .style(container::bordered_box)
+            .into()
+    }
+
+    fn view_signal_picker(&self, picker: &SignalPicker) -> Element<'_, Message> {
+        let signals = column(picker.signals.iter().enumerate().map(|(i, signal)| {
+            button(text!("{:>2}  {signal}", signal.raw()).font(Font::MONOSPACE))
+                .width(Length::Fill)
+                .style(if i == picker.cursor {
+                    button::primary
+                } else {
+                    button::text
+                })
+                .on_press(Message::PickSignal(*signal))
+                .into()
+        }));
+
+        let dialog = container(
+            column![
+                text!(
+                    "Send signal to {}",
+                    self.describe_signal_target(&picker.target)
+                ),
+                scrollable(signals)
+                    .id(SIGNAL_PICKER_ID)
+                    .height(Length::Fixed(300.0)),
+                text("Up/Down, Enter: choose, Esc: cancel")
+                    .style(text::secondary)
+                    .size(12),
+            ]
+            .spacing(8),
+        )
+        .width(Length::Fixed(320.0))
+        .padding(10)
+        .style(container::bordered_box);
+
+        modal(dialog)
+    }
+
+    fn view_priority_dialog<'a>(&'a self, dialog: &'a PriorityDialog) -> Element<'a, Message> {
+        let label = |s| text(s).width(Length::Fixed(80.0));
+        let value = |v: String| text(v).font(Font::MONOSPACE).width(Length::Fixed(30.0));
+
+        let mut form = column![
+            text!(
+                "Priority of {}",
+                self.describe_signal_target(&dialog.target)
+            ),
+            row![
+                label("Nice"),
+                slider(priority::NICE_RANGE, dialog.nice, Message::SetNice),
+                value(dialog.nice.to_string()),
+            ]
+            .spacing(12)
+            .align_y(Vertical::Center),
+            row![
+                label("I/O class"),
+                pick_list(
+                    &IoClass::ALL[..],
+                    Some(dialog.io_priority.class),
+                    Message::SetIoClass
+                ),
+            ]
+            .spacing(12)
+            .align_y(Vertical::Center),
+        ]
+        .spacing(8);
+
+        if dialog.io_priority.class.has_levels() {
+            form = form.push(
+                row![
+                    label("I/O level"),
+                    slider(
+                        priority::IO_LEVEL_RANGE,
+                        dialog.io_priority.level,
+                        Message::SetIoLevel
+                    ),
+                    value(dialog.io_priority.level.to_string()),
+                ]
+                .spacing(12)
+                .align_y(Vertical::Center),
+            );
+        }
+
+        let form = form
+            .push(
+                text("Lower values mean higher priority. Raising priority usually requires root.")
+                    .style(text::secondary)
+                    .size(12),
+            )
+            .push(
+                row![
+                    button("Apply").style(button::primary).on_press_maybe(
+                        (dialog.nice_changed || dialog.io_priority_changed)
+                            .then_some(Message::Enter)
+                    ),
+                    button("Cancel")
+                        .style(button::secondary)
+                        .on_press(Message::Back),
+                ]
+                .spacing(8),
+            );
+
+        modal(
+            container(form)
+                .width(Length::Fixed(400.0))
+                .padding(10)
+                .style(container::bordered_box),
+        )
+    }
+
+    fn view_affinity_dialog<'a>(&'a self, dialog: &'a AffinityDialog) -> Element<'a, Message> {
+        let num_cpus = affinity::num_cpus();
+        let parsed = CpuList::parse(&dialog.text, num_cpus);
+
+        let input = text_input("e.g. 0-3,8 or !0", &dialog.text)
+            .id(AFFINITY_INPUT_ID)
+            .on_input(Message::SetAffinityText);
+        let hint = match &parsed {
+            Ok(cpus) => text!("{} of {num_cpus} CPUs", cpus.count()).style(text::secondary),
+            Err(err) => text(err.clone()).style(text::danger),
+        }
+        .size(12);
+
+        let dialog = column![
+            text!(
+                "CPU affinity of {}",
+                self.describe_signal_target(&dialog.target)
+            ),
+            column![input, hint].spacing(2),
+            checkbox(dialog.all_threads)
+                .label("Apply to all threads")
+                .on_toggle(Message::SetAffinityAllThreads),
+            row![
+                button("Apply")
+                    .style(button::primary)
+                    .on_press_maybe(parsed.is_ok().then_some(Message::Enter)),
+                button("Cancel")
+                    .style(button::secondary)
+                    .on_press(Message::Back),
+            ]
+            .spacing(8),
+        ]
+        .spacing(8);
+
+        modal(
+            container(dialog)
+                .width(Length::Fixed(400.0))
+                .padding(10)
+                .style(container::bordered_box),
+        )
+    }
+
+    fn view_path_users_dialog<'a>(&'a self, path: &'a str) -> Element<'a, Message> {
+        let input = text_input("e.g. /mnt/usb or ~/project", path)
+            .id(PATH_USERS_INPUT_ID)
+            .on_input(Message::SetPathUsersText);
+        let hint =
+            text("Matches the file, anything inside a directory, or anything on a mount point")
+                .style(text::secondary)
+                .size(12);
+
+        let dialog = column![
+            text("Who is using this path?"),
+            column![input, hint].spacing(2),
+            row![
+                button("Search")
+                    .style(button::primary)
+                    .on_press_maybe((!path.trim().is_empty()).then_some(Message::Enter)),
+                button("Cancel")
+                    .style(button::secondary)
+                    .on_press(Message::Back),
+            ]
+            .spacing(8),
+        ]
+        .spacing(8);
+
+        modal(
+            container(dialog)
+                .width(Length::Fixed(400.0))
+                .padding(10)
+                .style(container::bordered_box),
+        )
+    }
+
+    fn view_column_chooser<'a>(&'a self, chooser: &'a ColumnChooser) -> Element<'a, Message> {
+        let hidden = ColumnKind::ALL
+            .into_iter()
+            .filter(|kind| !self.columns.iter().any(|column| column.kind == *kind))
+            .map(Column::new);
+        let entries = self.columns.iter().copied().map(|c| (c, true));
+        let entries = entries.chain(hidden.map(|c| (c, false)));
+        let last = self.columns.len() - 1;
+
+        let list = column(entries.enumerate().map(|(i, (col, is_visible))| {
+            let kind = col.kind;
+            // At least one column has to stay.
+            let can_toggle = !is_visible || self.columns.len() > 1;
+            let mut entry = row![
+                checkbox(is_visible)
+                    .label(kind.to_string())
+                    .on_toggle_maybe(can_toggle.then_some(move |v| Message::ToggleColumn(kind, v)))
+                    .width(Length::Fixed(120.0)),
+            ]
+            .spacing(6)
+            .align_y(Vertical::Center);
+            if is_visible {
+                entry = entry.push(
+                    row![
+                        button("↑")
+                            .style(button::text)
+                            .on_press_maybe((i > 0).then_some(Message::MoveColumn(kind, -1))),
+                        button("↓")
+                            .style(button::text)
+                            .on_press_maybe((i < last).then_some(Message::MoveColumn(kind, 1))),
+                        slider(Column::MIN_WIDTH..=Column::MAX_WIDTH, col.width, move |w| {
+                            Message::SetColumnWidth(kind, w)
+                        })
+                        .step(0.01)
+                        .on_release(Message::SaveColumns)
+                        .width(Length::Fixed(150.0)),
+                    ]
+                    .align_y(Vertical::Center),
+                );
+            }
+            entry.into()
+        }))
+        .spacing(2);
+
+        let mut dialog = column![
+            text("Columns"),
+            text!(
+                "Order and widths are saved to {}",
+                config::columns_path().unwrap_or_default().display()
+            )
+            .style(text::secondary)
+            .size(12),
+            scrollable(list).height(Length::Fixed(400.0)),
+        ]
+        .spacing(8);
+        if let Some(err) = &chooser.save_error {
+            dialog = dialog.push(text!("Couldn't save: {err}").style(text::danger).size(12));
+        }
+        let dialog = dialog.push(
+            row![
+                button("Reset")
+                    .style(button::secondary)
+                    .on_press(Message::ResetColumns),
+                button("Close")
+                    .style(button::primary)
+                    .on_press(Message::Back),
+            ]
+            .spacing(8),
+        );
+
+        modal(
+            container(dialog)
+                .width(Length::Fixed(400.0))
+                .padding(10)
+                .style(container::bordered_box),
+        )
+    }
+
+    fn view_confirmation<'a>(&'a self, staged: &'a StagedSignal) -> Element<'a, Message> {
+        let processes: Vec<_> = staged
+            .processes
+            .iter()
+            .filter_map(|process| self.last_data.get(process.key.pid))
+            .collect();
+
+        let title = if staged.escalate {
+            text!(
+                "Send {} to {}, then SIGKILL after {}s (Ctrl+=/Ctrl+- to change)?",
+                staged.signal,
+                self.describe_signal_target(&staged.target),
+                self.grace_period.as_secs()
+            )
+        } else {
+            text!(
+                "Send {} to {}?",
+                staged.signal,
+                self.describe_signal_target(&staged.target)
+            )
+        };
+
+        let other_users = signal::owners_to_warn_about(
+            processes.iter().map(|ps| (ps.uid, ps.user.as_ref())),
+            rustix::process::getuid().as_raw(),
+        );
+
+        let list = column(processes.iter().map(|ps| {
+            row![
+                text!("{:>7}", ps.pid).font(Font::MONOSPACE),
+                text(&ps.name).width(Length::Fixed(150.0)),
+                text(ps.user.as_ref()).width(Length::Fixed(80.0)),
+                text(&ps.command).wrapping(text::Wrapping::None),
+            ]
+            .spacing(12)
+            .into()
+        }));
+
+        let totals = text!(
+            "{} processes, {} memory, {:.1}% CPU",
+            processes.len(),
+            bytesize::ByteSize(processes.iter().map(|ps| ps.mem_usage_bytes).sum()),
+            processes.iter().map(|ps| ps.cpu_usage_percent).sum::<f32>()
+        );
+
+        let buttons = row![
+            button(text!("Send {}", staged.signal))
+                .style(button::danger)
+                .on_press(Message::Enter),
+            button("Cancel")
+                .style(button::secondary)
+                .on_press(Message::Back),
+        ]
+        .spacing(8);
+
+        let mut dialog = column![title].spacing(8);
+        if !other_users.is_empty() {
+            dialog = dialog.push(
+                text!(
+                    "Warning: includes processes owned by {}",
+                    other_users.join(", ")
+                )
+                .style(text::danger),
+            );
+        }
+        let dialog = dialog.push(scrollable(list).height(Length::Fixed(200.0)));
+        let dialog = dialog.push(totals).push(buttons);
+
+        modal(
+            container(dialog)
+                .max_width(800)
+                .padding(10)
+                .style(container::bordered_box),
+        )
+    }
+
     pub fn filter_rows(&mut self) {
         self.rows.clear();
+        self.search.refresh_query();
         let a = self.last_data.clone();
         let a = match &self.search.is_hidden {
-            true => a.search(&self.search.text),
+            true => a.search(&self.search.query),
+            false => a,
+        };
+        let a = match self.tree.enabled {
+            true => a.into_tree(&self.tree.collapsed_pids),
             false => a,
         };
         let a: Vec<Row> = a.into();
         self.rows.extend(a);
+
+        if let Some(pid) = self.selected_pid {
+            match self.rows.iter_mut().find(|row| row.pid == pid) {
+                Some(row) => row.is_selected = true,
+                None => self.selected_pid = None, // process is gone or filtered out
+            }
+        }
+        // Details of a newly selected process are read in the background, see
+        // `load_details_later`.
+        if self.selected_pid.is_none() {
+            self.details = None;
+        }
+
+        // Forget marks of processes that have exited.
+        self.marked.retain(|key| self.last_data.contains(key));
+        for row in &mut self.rows {
+            row.is_marked = self.marked.contains(&row.key());
+        }
     }
 
     pub fn sort_rows(&mut self) {
-        let ProcessListSort { column, order } = self.sort;
-        self.last_data.sort_by_column(column, order);
+        self.last_data.sort(&self.sort);
     }
 
     pub fn set_freeze(&mut self, enable: bool) {
@@ -372,62 +1344,730 @@ impl App {
         }
     }
 
-    pub fn sig_all_filtered(&mut self, sig: rustix::process::Signal) {
-        let pids: Vec<i32> = self.rows.iter().map(|x| x.pid).collect();
-        for pid in pids {
-            print!("sending {sig:?} to {pid} ... ");
+    pub fn move_selection(&mut self, movement: SelectionMove) -> Task<Message> {
+        /// Rows skipped by PageUp/PageDown.
+        const PAGE_ROWS: usize = 20;
 
-            let result = kill_process(
-                rustix::thread::Pid::from_raw(pid).expect("pid in table should be valid"),
-                sig,
-            );
-            match result {
-                Ok(()) => {
-                    println!("ok");
+        let Some(last) = self.rows.len().checked_sub(1) else {
+            return Task::none();
+        };
+        let current = self
+            .selected_pid
+            .and_then(|pid| self.rows.iter().position(|row| row.pid == pid));
+
+        let idx = movement.apply(current, last, PAGE_ROWS);
+
+        self.selected_pid = Some(self.rows[idx].pid);
+        self.filter_rows();
+
+        // Rows have the same height, so relative offset keeps the selected row in view.
+        let scroll = widget::operation::snap_to(
+            self.table_top_id.clone(),
+            scrollable::RelativeOffset {
+                x: 0.,
+                y: if last == 0 {
+                    0.
+                } else {
+                    idx as f32 / last as f32
+                },
+            },
+        );
+        Task::batch([scroll, self.load_details_later()])
+    }
+
+    fn has_details_of(&self, pid: i32) -> bool {
+        self.details
+            .as_ref()
+            .is_some_and(|details| details.pid == pid)
+    }
+
+    /// Reads details of the selected process once the selection stays on it for a moment, so
+    /// that holding Up/Down doesn't read every row on the way.
+    fn load_details_later(&self) -> Task<Message> {
+        const DELAY: Duration = Duration::from_millis(150);
+
+        match self.selected_pid {
+            Some(pid) if !self.has_details_of(pid) => {
+                Task::perform(tokio::time::sleep(DELAY), move |()| {
+                    Message::LoadDetails(pid)
+                })
+            }
+            _ => Task::none(),
+        }
+    }
+
+    fn toggle_mark(&mut self, pid: i32) {
+        let Some(row) = self.rows.iter().find(|row| row.pid == pid) else {
+            return;
+        };
+        let key = row.key();
+        if !self.marked.remove(&key) {
+            self.marked.insert(key);
+        }
+    }
+
+    fn stage_signal(&mut self, signal: KillSignal, target: SignalTarget, escalate: bool) {
+        // Marks and selection survive refreshes, but freeze anyway, so rows don't move around
+        // while the user confirms.
+        self.set_freeze(true);
+        self.staged_signal = Some(StagedSignal {
+            signal,
+            processes: self.target_processes(&target),
+            target,
+            escalate,
+        });
+    }
+
+    /// Opens handles for processes as they are shown in the (frozen) table.
+    fn target_processes(&self, target: &SignalTarget) -> Vec<ProcessHandle> {
+        let pids = match target {
+            SignalTarget::AllFiltered => self.rows.iter().map(|x| x.pid).collect(),
+            SignalTarget::Selected(pid) => vec![*pid],
+            SignalTarget::Marked => self.marked.iter().map(|key| key.pid).collect(),
+            SignalTarget::Subtree(pid) => self.last_data.subtree(*pid, Some(killa_pid())),
+            // Checked to be readable when staging, see `selected_cgroup`.
+            SignalTarget::Cgroup(cgroup) => self.cgroupfs.members(cgroup).unwrap_or_default(),
+        };
+        pids.into_iter()
+            .filter_map(|pid| self.last_data.get(pid))
+            .map(|ps| {
+                let key = ProcessKey {
+                    pid: ps.pid,
+                    start_time: ps.start_time,
+                };
+                ProcessHandle::open(key, ps.name.clone())
+            })
+            .collect()
+    }
+
+    fn start_escalation(&mut self, processes: Vec<ProcessHandle>) {
+        self.escalation = Some(Escalation::start(processes, self.grace_period));
+
+        self.set_freeze(false);
+        self.sort_rows();
+        self.filter_rows();
+    }
+
+    /// Processes that a signal staged right now would be sent to.
+    fn signal_target(&self) -> Option<SignalTarget> {
+        if !self.marked.is_empty() {
+            Some(SignalTarget::Marked)
+        } else if let Some(pid) = self.selected_pid {
+            Some(SignalTarget::Selected(pid))
+        // With syntax errors, filtered processes are likely not what the user intended.
+        } else if matches!(self.freeze, FreezeState::Enabled(_))
+            && self.search.text.len() >= 3
+            && self.search.query.errors().is_empty()
+        {
+            Some(SignalTarget::AllFiltered)
+        } else {
+            None
+        }
+    }
+
+    /// Handles messages while the signal picker is open. Keys not used by the picker are ignored,
+    /// so that typing doesn't change the search behind it.
+    fn update_signal_picker(&mut self, message: Message) -> Task<Message> {
+        let Some(picker) = &mut self.signal_picker else {
+            return Task::none();
+        };
+
+        match message {
+            Message::MoveSelection(movement) => {
+                // The list is short, so PageUp/PageDown go all the way.
+                let last = picker.signals.len() - 1;
+                picker.cursor = movement.apply(Some(picker.cursor), last, last);
+                return self.scroll_signal_picker();
+            }
+            Message::Enter => {
+                let signal = picker.signals[picker.cursor];
+                return self.update_signal_picker(Message::PickSignal(signal));
+            }
+            Message::PickSignal(signal) => {
+                let target = picker.target.clone();
+                self.signal_picker = None;
+                self.last_picked_signal = signal;
+                self.stage_signal(signal, target, false);
+            }
+            Message::Back => {
+                self.signal_picker = None;
+                self.set_freeze(false);
+                self.sort_rows();
+                self.filter_rows();
+            }
+            _ => {}
+        }
+
+        Task::none()
+    }
+
+    /// Handles messages while the priority dialog is open, see [`Self::update_signal_picker`].
+    fn update_priority_dialog(&mut self, message: Message) -> Task<Message> {
+        let Some(dialog) = &mut self.priority_dialog else {
+            return Task::none();
+        };
+
+        match message {
+            Message::SetNice(nice) => {
+                dialog.nice = nice;
+                dialog.nice_changed = true;
+            }
+            Message::SetIoClass(class) => {
+                dialog.io_priority.class = class;
+                dialog.io_priority_changed = true;
+            }
+            Message::SetIoLevel(level) => {
+                dialog.io_priority.level = level;
+                dialog.io_priority_changed = true;
+            }
+            Message::Enter if dialog.nice_changed || dialog.io_priority_changed => {
+                let PriorityDialog {
+                    processes,
+                    nice,
+                    io_priority,
+                    nice_changed,
+                    io_priority_changed,
+                    ..
+                } = self.priority_dialog.take().expect("dialog is open");
+
+                let mut actions = vec![];
+                if nice_changed {
+                    actions.push(format!("Nice {nice}"));
+                }
+                if io_priority_changed {
+                    actions.push(format!("I/O {io_priority}"));
+                }
+                // Each value is set and reported on its own, e.g. raising nice may be denied
+                // while I/O priority can still be lowered.
+                let mut results = vec![];
+                for process in &processes {
+                    let result = |what: &str, outcome| ActionResult {
+                        key: process.key,
+                        name: format!("{} ({what})", process.name),
+                        outcome,
+                    };
+                    if nice_changed {
+                        let outcome = process.apply(|pid| priority::set_nice(pid, nice));
+                        results.push(result("nice", outcome));
+                    }
+                    if io_priority_changed {
+                        let outcome = process.apply(|pid| io_priority.set(pid));
+                        results.push(result("I/O", outcome));
+                    }
+                }
+                self.show_action_results(actions.join(", "), results);
+                self.set_freeze(false);
+            }
+            Message::Back => {
+                self.priority_dialog = None;
+                self.set_freeze(false);
+                self.sort_rows();
+                self.filter_rows();
+            }
+            _ => {}
+        }
+
+        Task::none()
+    }
+
+    /// Handles messages while the affinity dialog is open, see [`Self::update_signal_picker`].
+    fn update_affinity_dialog(&mut self, message: Message) -> Task<Message> {
+        let Some(dialog) = &mut self.affinity_dialog else {
+            return Task::none();
+        };
+
+        match message {
+            Message::SetAffinityText(text) => dialog.text = text,
+            Message::SetAffinityAllThreads(all_threads) => dialog.all_threads = all_threads,
+            Message::Enter => {
+                let Ok(cpus) = CpuList::parse(&dialog.text, affinity::num_cpus()) else {
+                    return Task::none();
+                };
+                let AffinityDialog {
+                    processes,
+                    all_threads,
+                    ..
+                } = self.affinity_dialog.take().expect("dialog is open");
+                let results = processes
+                    .iter()
+                    .map(|process| ActionResult {
+                        key: process.key,
+                        name: process.name.clone(),
+                        outcome: process.apply(|pid| {
+                            if all_threads {
+                                cpus.set_all_threads(pid)
+                            } else {
+                                cpus.set(pid)
+                            }
+                        }),
+                    })
+                    .collect();
+                self.show_action_results(format!("Affinity {cpus}"), results);
+                self.set_freeze(false);
+            }
+            Message::Back => {
+                self.affinity_dialog = None;
+                self.set_freeze(false);
+                self.sort_rows();
+                self.filter_rows();
+            }
+            _ => {}
+        }
+
+        Task::none()
+    }
+
+    /// Handles messages while the path users dialog is open, see [`Self::update_signal_picker`].
+    fn update_path_users_dialog(&mut self, message: Message) -> Task<Message> {
+        let Some(path) = &mut self.path_users_dialog else {
+            return Task::none();
+        };
+
+        match message {
+            Message::SetPathUsersText(text) => *path = text,
+            Message::Enter if !path.trim().is_empty() => {
+                let path = self.path_users_dialog.take().expect("dialog is open");
+                // Quoted, so that paths with spaces stay a single search term.
+                let escaped = path.trim().replace('\\', "\\\\").replace('"', "\\\"");
+                return self.handle_search(TextInputAction::Replace(format!("file:\"{escaped}\"")));
+            }
+            Message::Back => self.path_users_dialog = None,
+            _ => {}
+        }
+
+        Task::none()
+    }
+
+    /// Handles messages while the column chooser is open, see [`Self::update_signal_picker`].
+    fn update_column_chooser(&mut self, message: Message) -> Task<Message> {
+        let position = |columns: &[Column], kind| columns.iter().position(|c| c.kind == kind);
+
+        match message {
+            Message::ToggleColumn(kind, true) if position(&self.columns, kind).is_none() => {
+                self.columns.push(Column::new(kind));
+                self.save_columns();
+                // Don't wait for the next refresh.
+                self.last_data.read_columns(&self.columns);
+                self.filter_rows();
+            }
+            // At least one column has to stay.
+            Message::ToggleColumn(kind, false) if self.columns.len() > 1 => {
+                self.columns.retain(|column| column.kind != kind);
+                self.save_columns();
+            }
+            Message::MoveColumn(kind, delta) => {
+                if let Some(i) = position(&self.columns, kind)
+                    && let Some(j) = i.checked_add_signed(delta)
+                    && j < self.columns.len()
+                {
+                    self.columns.swap(i, j);
+                    self.save_columns();
                 }
-                Err(err) => {
-                    println!("error: {err}");
+            }
+            Message::SetColumnWidth(kind, width) => {
+                if let Some(i) = position(&self.columns, kind) {
+                    self.columns[i].width = width.clamp(Column::MIN_WIDTH, Column::MAX_WIDTH);
                 }
             }
+            Message::SaveColumns => self.save_columns(),
+            Message::ResetColumns => {
+                self.columns = Column::defaults();
+                self.save_columns();
+                self.last_data.read_columns(&self.columns);
+                self.filter_rows();
+            }
+            Message::Back => self.column_chooser = None,
+            _ => {}
+        }
+
+        Task::none()
+    }
+
+    fn save_columns(&mut self) {
+        let result = config::save_columns(&self.columns);
+        if let Some(chooser) = &mut self.column_chooser {
+            chooser.save_error = result.err().map(|err| err.to_string());
+        }
+    }
+
+    fn scroll_signal_picker(&self) -> Task<Message> {
+        let Some(picker) = &self.signal_picker else {
+            return Task::none();
+        };
+        let last = picker.signals.len() - 1;
+        widget::operation::snap_to(
+            SIGNAL_PICKER_ID,
+            scrollable::RelativeOffset {
+                x: 0.,
+                y: picker.cursor as f32 / last.max(1) as f32,
+            },
+        )
+    }
+
+    fn describe_signal_target(&self, target: &SignalTarget) -> String {
+        match *target {
+            SignalTarget::AllFiltered => format!("all {} filtered processes", self.rows.len()),
+            SignalTarget::Selected(pid) => match self.rows.iter().find(|row| row.pid == pid) {
+                Some(row) => format!("{} ({pid})", row.program_name),
+                None => format!("process {pid}"),
+            },
+            SignalTarget::Marked => format!("{} marked processes", self.marked.len()),
+            SignalTarget::Subtree(pid) => {
+                let count = self.last_data.subtree(pid, Some(killa_pid())).len();
+                let skipped = if count < self.last_data.subtree(pid, None).len() {
+                    ", skipping killa and its parents"
+                } else {
+                    ""
+                };
+                format!(
+                    "{} and its descendants ({count} processes{skipped})",
+                    self.describe_signal_target(&SignalTarget::Selected(pid)),
+                )
+            }
+            SignalTarget::Cgroup(ref cgroup) => format!("cgroup {cgroup}"),
+        }
+    }
 
-            // refresh/refreeze
-            self.set_freeze(false);
-            self.sort_rows();
-            self.filter_rows();
-            self.set_freeze(true);
+    /// Cgroup of the selected process, unless `sig` can't be applied to it.
+    fn selected_cgroup(&self, sig: KillSignal) -> Result<String, String> {
+        let pid = self.selected_pid.ok_or("no process selected")?;
+        let cgroup = CgroupFs::process_cgroup(pid)
+            .map_err(|err| err.to_string())?
+            .ok_or("the system uses cgroup v1, only v2 is supported")?;
+        if cgroup == "/" {
+            return Err("the process is in the root cgroup".to_string());
+        }
+        // Frozen or killed, killa couldn't thaw the group again.
+        if matches!(sig, KillSignal::Named(Signal::Kill | Signal::Stop))
+            && let Ok(Some(own)) = CgroupFs::process_cgroup(killa_pid())
+            && cgroup::is_within(&own, &cgroup)
+        {
+            return Err(format!("killa itself runs in {cgroup}"));
         }
+        self.cgroupfs
+            .members(&cgroup)
+            .map_err(|err| format!("can't list processes in {cgroup}: {err}"))?;
+        Ok(cgroup)
+    }
+
+    /// Kills, freezes or thaws a whole cgroup for SIGKILL, SIGSTOP and SIGCONT respectively.
+    /// Other signals are sent to the processes one by one.
+    fn apply_to_cgroup(&mut self, sig: KillSignal, cgroup: &str, processes: Vec<ProcessHandle>) {
+        let result = match sig {
+            KillSignal::Named(Signal::Kill) => self.cgroupfs.kill(cgroup),
+            KillSignal::Named(Signal::Stop) => self.cgroupfs.set_frozen(cgroup, true),
+            KillSignal::Named(Signal::Cont) => self.cgroupfs.set_frozen(cgroup, false),
+            _ => return self.send_signal(sig, processes),
+        };
+        let action = format!("{} cgroup {cgroup}", cgroup_action(sig));
+        self.show_group_result(action, result.into(), processes);
+        self.refresh_frozen();
+    }
+
+    /// Shows the outcome of an action on a whole group once, listing the processes in it.
+    /// Unlike [`Self::show_action_results`], the table is left as is, e.g. when nothing was done.
+    fn show_group_result(
+        &mut self,
+        action: String,
+        outcome: SignalOutcome,
+        processes: Vec<ProcessHandle>,
+    ) {
+        let results = processes
+            .into_iter()
+            .map(|process| ActionResult {
+                key: process.key,
+                name: process.name,
+                outcome: outcome.clone(),
+            })
+            .collect();
+        self.action_results = Some(ActionResults {
+            action,
+            group_outcome: Some(outcome),
+            results,
+        });
+    }
+
+    /// Signals a process and all of its descendants, children before parents.
+    ///
+    /// The whole subtree is stopped first, so that supervisors can't respawn workers in between.
+    pub fn send_signal_subtree(&mut self, sig: KillSignal, processes: Vec<ProcessHandle>) {
+        let (stop, cont) = (
+            KillSignal::Named(Signal::Stop),
+            KillSignal::Named(Signal::Cont),
+        );
+
+        // Processes that were already stopped, e.g. paused by the user, are left stopped.
+        let stopped_here: Vec<_> = processes
+            .iter()
+            .filter(|process| !process.key.is_stopped())
+            .collect();
+        let mut results = Self::signal_processes(stop, processes.iter().rev());
+        if sig != stop {
+            results = Self::signal_processes(sig, &processes);
+        }
+        // Stopped processes don't act on most signals until continued.
+        if sig != stop && sig != KillSignal::Named(Signal::Kill) {
+            Self::signal_processes(cont, stopped_here);
+        }
+        self.show_signal_results(sig, results);
+    }
+
+    pub fn send_signal(&mut self, sig: KillSignal, processes: Vec<ProcessHandle>) {
+        let results = Self::signal_processes(sig, &processes);
+        self.show_signal_results(sig, results);
+    }
+
+    fn signal_processes<'a>(
+        sig: KillSignal,
+        processes: impl IntoIterator<Item = &'a ProcessHandle>,
+    ) -> Vec<ActionResult> {
+        processes
+            .into_iter()
+            .map(|process| ActionResult {
+                key: process.key,
+                name: process.name.clone(),
+                outcome: process.send(sig),
+            })
+            .collect()
+    }
+
+    fn show_signal_results(&mut self, signal: KillSignal, results: Vec<ActionResult>) {
+        for result in results.iter().filter(|result| result.outcome.is_sent()) {
+            match signal.pauses() {
+                Some(true) => self.paused.insert(result.key),
+                Some(false) => self.paused.remove(&result.key),
+                None => false,
+            };
+        }
+
+        self.show_action_results(signal.to_string(), results);
+    }
+
+    fn show_action_results(&mut self, action: String, results: Vec<ActionResult>) {
+        self.action_results = Some(ActionResults {
+            action,
+            group_outcome: None,
+            results,
+        });
+        self.refresh_frozen();
+    }
+
+    fn refresh_frozen(&mut self) {
+        // refresh/refreeze
+        self.set_freeze(false);
+        self.sort_rows();
+        self.filter_rows();
+        self.set_freeze(true);
+    }
+}
+
+/// How a signal is applied to a whole cgroup, see [`App::apply_to_cgroup`].
+fn cgroup_action(sig: KillSignal) -> String {
+    match sig {
+        KillSignal::Named(Signal::Kill) => "Kill".to_string(),
+        KillSignal::Named(Signal::Stop) => "Freeze".to_string(),
+        KillSignal::Named(Signal::Cont) => "Thaw".to_string(),
+        _ => sig.to_string(),
     }
 }
 
+/// Reads details off the UI thread, as sockets, file descriptors and such take a while.
+fn read_details(pid: i32) -> Task<Message> {
+    Task::perform(
+        tokio::task::spawn_blocking(move || ProcessDetails::read(pid)),
+        Result::ok,
+    )
+    .and_then(|details| Task::done(Message::DetailsLoaded(details)))
+}
+
+fn killa_pid() -> i32 {
+    std::process::id() as i32
+}
+
+/// Shows the dialog over the rest of the window, which is dimmed and closes the dialog on click.
+fn modal<'a>(dialog: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+    opaque(
+        mouse_area(center(opaque(dialog)).style(|_theme| {
+            background(Color {
+                a: 0.6,
+                ..Color::BLACK
+            })
+        }))
+        .on_press(Message::Back),
+    )
+}
+
 impl Default for App {
     fn default() -> Self {
         Self {
-            columns: vec![
-                ColumnKind::Name,
-                ColumnKind::Memory,
-                ColumnKind::Cpu,
-                ColumnKind::Pid,
-                // ColumnKind::CpuTime, // todo
-                // ColumnKind::Started, // todo
-                ColumnKind::Command,
-            ],
+            columns: Column::defaults(),
             rows: vec![],
             table_top_id: widget::Id::unique(),
             theme: Theme::Dark, // whatever startup theme, will be changed shortly.
-            search: SearchState {
-                is_hidden: false,
-                text: String::new(),
-            },
-            sort: ProcessListSort {
-                column: ColumnKind::Cpu,
-                order: SortOrder::default(),
-            },
+            search: SearchState::default(),
+            sort: ProcessListSort::new(ColumnKind::Cpu, SortOrder::default()),
             last_data: KillaData::default(),
             freeze: FreezeState::Disabled,
             wireframe_enabled: false,
-            staged_sig_all_filtered: None,
+            staged_signal: None,
+            tree: TreeState::default(),
+            selected_pid: None,
+            marked: HashSet::new(),
+            modifiers: keyboard::Modifiers::empty(),
+            signal_picker: None,
+            last_picked_signal: KillSignal::default(),
+            escalation: None,
+            grace_period: escalation::DEFAULT_GRACE_PERIOD,
+            action_results: None,
+            paused: HashSet::new(),
+            priority_dialog: None,
+            affinity_dialog: None,
+            path_users_dialog: None,
+            column_chooser: None,
+            cgroupfs: CgroupFs::default(),
+            details: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use escalation::EscalationEntry;
+
+    fn staged_signal() -> StagedSignal {
+        StagedSignal {
+            signal: Signal::Term.into(),
+            target: SignalTarget::Marked,
+            processes: vec![],
+            escalate: false,
         }
     }
+
+    /// Spawned process, with a handle as if it was picked in the table.
+    fn spawn_sleep() -> (std::process::Child, ProcessHandle) {
+        let child = std::process::Command::new("sleep")
+            .arg("60")
+            .spawn()
+            .unwrap();
+        let pid = child.id() as i32;
+        let stat = procfs::process::Process::new(pid).unwrap().stat().unwrap();
+        let key = ProcessKey {
+            pid,
+            start_time: stat.starttime,
+        };
+        (child, ProcessHandle::open(key, "sleep".to_string()))
+    }
+
+    fn wait_until(what: &str, condition: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "timed out waiting until {what}");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_subtree_signal_keeps_paused_processes_stopped() {
+        let (mut paused, paused_handle) = spawn_sleep();
+        let (mut running, running_handle) = spawn_sleep();
+        assert!(paused_handle.send(Signal::Stop.into()).is_sent());
+        wait_until("paused", || paused_handle.key.is_stopped());
+
+        let mut app = App::default();
+        // SIGWINCH is ignored by default, so both processes survive it.
+        app.send_signal_subtree(
+            Signal::Winch.into(),
+            vec![paused_handle.clone(), running_handle.clone()],
+        );
+        assert!(paused_handle.key.is_stopped());
+        assert!(running_handle.key.is_running());
+        assert!(!running_handle.key.is_stopped());
+
+        for child in [&mut paused, &mut running] {
+            child.kill().unwrap();
+            child.wait().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_back_cancels_staged_signal_before_panels() {
+        let mut app = App {
+            escalation: Some(Escalation::start(vec![], escalation::DEFAULT_GRACE_PERIOD)),
+            action_results: Some(ActionResults {
+                action: "SIGTERM".to_string(),
+                group_outcome: None,
+                results: vec![],
+            }),
+            staged_signal: Some(staged_signal()),
+            ..Default::default()
+        };
+
+        let _ = app.update(Message::Back);
+        assert!(app.staged_signal.is_none());
+        assert!(app.escalation.is_some());
+        assert!(app.action_results.is_some());
+
+        let _ = app.update(Message::Back);
+        assert!(app.escalation.is_none());
+    }
+
+    #[test]
+    fn test_details_of_previous_selection_are_dropped() {
+        let mut app = App {
+            selected_pid: Some(killa_pid()),
+            ..Default::default()
+        };
+
+        let _ = app.update(Message::DetailsLoaded(ProcessDetails::read(1)));
+        assert!(app.details.is_none());
+
+        let _ = app.update(Message::DetailsLoaded(ProcessDetails::read(killa_pid())));
+        assert!(app.has_details_of(killa_pid()));
+    }
+
+    #[test]
+    fn test_cgroup_error_keeps_table_unfrozen() {
+        let mut app = App::default();
+
+        let _ = app.update(Message::StageSignalCgroup(Signal::Kill.into()));
+        assert!(matches!(app.freeze, FreezeState::Disabled));
+        assert!(app.staged_signal.is_none());
+        let results = app.action_results.expect("error is reported");
+        assert!(matches!(
+            results.group_outcome,
+            Some(SignalOutcome::Other(_))
+        ));
+    }
+
+    #[test]
+    fn test_unfinished_escalation_is_kept() {
+        let process = ProcessHandle::open(
+            ProcessKey {
+                pid: 0,
+                start_time: 0,
+            },
+            String::new(),
+        );
+        let mut app = App {
+            escalation: Some(Escalation {
+                entries: vec![EscalationEntry {
+                    process,
+                    status: EscalationStatus::Signalled,
+                    error: None,
+                }],
+                started: Instant::now(),
+                grace_period: escalation::DEFAULT_GRACE_PERIOD,
+                escalated: None,
+            }),
+            selected_pid: Some(1),
+            ..Default::default()
+        };
+
+        let _ = app.update(Message::StageEscalatingKill);
+        assert!(app.staged_signal.is_none());
+        assert_eq!(app.escalation.as_ref().map(|e| e.entries.len()), Some(1));
+    }
 }
 
 // impl<'a> iced::Program for App<'a> {
@@ -0,0 +1,129 @@
+//! Process scheduling priority: nice value and I/O priority.
+
+use rustix::process::{Pid, setpriority_process};
+use std::fmt;
+
+pub const NICE_RANGE: std::ops::RangeInclusive<i32> = -20..=19;
+/// Levels of the realtime and best-effort I/O classes, 0 being the highest priority.
+pub const IO_LEVEL_RANGE: std::ops::RangeInclusive<u8> = 0..=7;
+
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IoClass {
+    /// Not set, the I/O priority is derived from the nice value.
+    #[default]
+    None,
+    Realtime,
+    BestEffort,
+    Idle,
+}
+
+impl IoClass {
+    pub const ALL: [IoClass; 4] = [
+        IoClass::None,
+        IoClass::Realtime,
+        IoClass::BestEffort,
+        IoClass::Idle,
+    ];
+
+    /// Only realtime and best-effort classes have levels.
+    pub fn has_levels(self) -> bool {
+        matches!(self, IoClass::Realtime | IoClass::BestEffort)
+    }
+}
+
+impl fmt::Display for IoClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IoClass::None => f.write_str("None"),
+            IoClass::Realtime => f.write_str("Realtime"),
+            IoClass::BestEffort => f.write_str("Best effort"),
+            IoClass::Idle => f.write_str("Idle"),
+        }
+    }
+}
+
+/// I/O scheduling class and level, see `ioprio_set(2)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IoPriority {
+    pub class: IoClass,
+    pub level: u8,
+}
+
+impl IoPriority {
+    pub fn get(pid: i32) -> std::io::Result<Self> {
+        // SAFETY: ioprio_get has no memory safety requirements.
+        let ret = unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, pid) };
+        if ret < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let ret = ret as libc::c_int;
+        let class = match ret >> IOPRIO_CLASS_SHIFT {
+            1 => IoClass::Realtime,
+            2 => IoClass::BestEffort,
+            3 => IoClass::Idle,
+            _ => IoClass::None,
+        };
+        Ok(Self {
+            class,
+            level: (ret & ((1 << IOPRIO_CLASS_SHIFT) - 1)) as u8,
+        })
+    }
+
+    pub fn set(self, pid: i32) -> std::io::Result<()> {
+        let class = match self.class {
+            IoClass::None => 0,
+            IoClass::Realtime => 1,
+            IoClass::BestEffort => 2,
+            IoClass::Idle => 3,
+        };
+        let level = if self.class.has_levels() {
+            self.level as libc::c_int
+        } else {
+            0
+        };
+        let ioprio = (class << IOPRIO_CLASS_SHIFT) | level;
+        // SAFETY: ioprio_set has no memory safety requirements.
+        match unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, pid, ioprio) } {
+            0 => Ok(()),
+            _ => Err(std::io::Error::last_os_error()),
+        }
+    }
+}
+
+impl fmt::Display for IoPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Same notation as `ionice`, abbreviated.
+        match self.class {
+            IoClass::None => f.write_str("-"),
+            IoClass::Realtime => write!(f, "rt/{}", self.level),
+            IoClass::BestEffort => write!(f, "be/{}", self.level),
+            IoClass::Idle => f.write_str("idle"),
+        }
+    }
+}
+
+pub fn set_nice(pid: i32, nice: i32) -> std::io::Result<()> {
+    let pid = Pid::from_raw(pid).expect("pid in table should be valid");
+    Ok(setpriority_process(Some(pid), nice)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_priority_roundtrip() {
+        let pid = std::process::id() as i32;
+        // Lowering priority of own process doesn't need any privileges.
+        let idle = IoPriority {
+            class: IoClass::BestEffort,
+            level: 7,
+        };
+        idle.set(pid).unwrap();
+        assert_eq!(IoPriority::get(pid).unwrap(), idle);
+        assert_eq!(idle.to_string(), "be/7");
+    }
+}
@@ -1,40 +1,205 @@
-use crate::ui::ColumnKind;
+use crate::affinity::CpuList;
+use crate::file_users::{FileUsers, PathQuery};
+use crate::priority::{IoClass, IoPriority};
+use crate::sockets::PortOwners;
 use crate::ui::Row;
+use crate::ui::TreeNode;
+use crate::ui::{Column, ColumnKind};
 use bottom::data_collection::memory::MemHarvest;
+use bottom::data_collection::processes::ProcessHarvest;
+use bottom::widgets::process_table::query::{ProcessQuery, QueryError, parse_query};
+use chrono::{DateTime, Local};
+use regex::{Regex, RegexBuilder};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
 
 #[derive(Clone, Default)]
 pub struct KillaData {
     // todo: optimization by keeping a field of ordered ones?
     rows: Vec<Row>,
-    // todo: should we keep the original data? I mean this will hold original data, no?
+    /// Collected processes by pid, with CPU usage normalized the same way as in [`Row`].
+    /// Used by bottom's query engine, which works on [`ProcessHarvest`] directly.
+    harvest: Arc<HashMap<i32, ProcessHarvest>>,
     pub memory: MemHarvest,
+    /// Shared between clones, so that it's read at most once per refresh and matches frozen
+    /// rows.
+    search_context: Arc<Mutex<SearchContext>>,
 }
 
 impl From<Box<bottom::data_collection::Data>> for KillaData {
     fn from(data: Box<bottom::data_collection::Data>) -> Self {
+        let mut harvest = HashMap::new();
         let rows = data
             .list_of_processes
             .unwrap_or_default()
-            .iter()
+            .into_iter()
             .enumerate()
-            .map(|(idx, ps)| Row {
-                row_index: idx,
-                program_name: ps.name.clone(),
-                program_name_lowercase: ps.name.to_lowercase(),
-                mem: ps.mem_usage_bytes / 1_000_000,
-                cpu_perc: (((ps.cpu_usage_percent) / (num_cpus::get() as f32) * 10.0) as i32)
-                    as f32
-                    / 10.0,
-                pid: ps.pid,
-                command: ps.command.clone(),
-                command_lowercase: ps.command.to_lowercase(),
-                cpu_time: ps.time,
+            .map(|(idx, mut ps)| {
+                ps.cpu_usage_percent = (((ps.cpu_usage_percent) / (num_cpus::get() as f32) * 10.0)
+                    as i32) as f32
+                    / 10.0;
+                let row = Row {
+                    row_index: idx,
+                    program_name: ps.name.clone(),
+                    program_name_lowercase: ps.name.to_lowercase(),
+                    mem: ps.mem_usage_bytes / 1_000_000,
+                    mem_perc: ps.mem_usage_percent,
+                    swap: None, // read only when shown, see `read_columns`
+                    cpu_perc: ps.cpu_usage_percent,
+                    pid: ps.pid,
+                    command: ps.command.clone(),
+                    command_lowercase: ps.command.to_lowercase(),
+                    user: ps.user.to_string(),
+                    read_per_sec: ps.read_bytes_per_sec,
+                    write_per_sec: ps.write_bytes_per_sec,
+                    total_read: ps.total_read_bytes,
+                    total_write: ps.total_write_bytes,
+                    threads: ps.threads,
+                    tty: ps.tty.clone(),
+                    age: ps.time,
+                    cpu_time: ps.cpu_time,
+                    children_cpu_time: ps.children_cpu_time,
+                    state_name: ps.process_state.0.clone(),
+                    state: ps.process_state.1,
+                    nice: ps.nice,
+                    // Read only when shown, see `read_columns`.
+                    io_priority: None,
+                    affinity: None,
+                    parent_pid: ps.parent_pid,
+                    tree: None,
+                    is_selected: false,
+                    is_marked: false,
+                    start_ticks: ps.start_time,
+                    started_at: ps.started_at.map(DateTime::<Local>::from),
+                };
+                harvest.insert(ps.pid, ps);
+                row
             })
             .collect();
         Self {
             rows,
+            harvest: Arc::new(harvest),
             memory: data.memory.unwrap_or_default(),
+            search_context: Default::default(),
+        }
+    }
+}
+
+/// Identifies a process. Unlike PID alone, it doesn't get reused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ProcessKey {
+    pub pid: i32,
+    /// Start time of the process in clock ticks since boot.
+    pub start_time: u64,
+}
+
+impl ProcessKey {
+    /// Checks the process directly in procfs, without waiting for the next collection.
+    /// Zombies are considered exited.
+    pub fn is_running(&self) -> bool {
+        self.stat().is_some_and(|stat| stat.state != 'Z')
+    }
+
+    /// Stopped by a signal, e.g. paused with SIGSTOP. See [`Row::is_stopped`].
+    pub fn is_stopped(&self) -> bool {
+        self.stat().is_some_and(|stat| stat.state == 'T')
+    }
+
+    /// Checks that the pid hasn't been reused by another process since this key was created.
+    pub fn is_same_process(&self) -> bool {
+        self.stat().is_some()
+    }
+
+    fn stat(&self) -> Option<procfs::process::Stat> {
+        procfs::process::Process::new(self.pid)
+            .and_then(|ps| ps.stat())
+            .ok()
+            .filter(|stat| stat.starttime == self.start_time)
+    }
+}
+
+impl KillaData {
+    /// Reads values of `columns` that aren't collected with the rest, as they take extra
+    /// syscalls for every process.
+    pub fn read_columns(&mut self, columns: &[Column]) {
+        let is_shown = |kind| columns.iter().any(|column| column.kind == kind);
+        let (swap, io_priority, affinity) = (
+            is_shown(ColumnKind::Swap),
+            is_shown(ColumnKind::IoPriority),
+            is_shown(ColumnKind::Affinity),
+        );
+        if !swap && !io_priority && !affinity {
+            return;
+        }
+        for row in &mut self.rows {
+            if swap {
+                // Kernel threads don't have swap.
+                row.swap = procfs::process::Process::new(row.pid)
+                    .and_then(|ps| ps.status())
+                    .map(|status| status.vmswap.unwrap_or(0) * 1024)
+                    .ok();
+            }
+            if io_priority {
+                row.io_priority = IoPriority::get(row.pid).ok();
+            }
+            if affinity {
+                row.affinity = CpuList::get(row.pid).ok();
+            }
+        }
+    }
+
+    pub fn get(&self, pid: i32) -> Option<&ProcessHarvest> {
+        self.harvest.get(&pid)
+    }
+
+    pub fn contains(&self, key: &ProcessKey) -> bool {
+        self.harvest
+            .get(&key.pid)
+            .is_some_and(|ps| ps.start_time == key.start_time)
+    }
+
+    /// Given pid and pids of all its descendants, ordered so that children come before their
+    /// parents. Filtered out processes are included too.
+    ///
+    /// `exclude` and its ancestors are left out, while their other descendants are kept. This
+    /// keeps killa from stopping itself, or the terminal it runs in, half way through.
+    pub fn subtree(&self, pid: i32, exclude: Option<i32>) -> Vec<i32> {
+        if !self.harvest.contains_key(&pid) {
+            return vec![];
+        }
+
+        let mut excluded = HashSet::new();
+        let mut ancestor = exclude;
+        while let Some(pid) = ancestor
+            && excluded.insert(pid)
+        {
+            ancestor = self.harvest.get(&pid).and_then(|ps| ps.parent_pid);
+        }
+
+        let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+        for ps in self.harvest.values() {
+            if let Some(ppid) = ps.parent_pid
+                && ppid != ps.pid
+            {
+                children.entry(ppid).or_default().push(ps.pid);
+            }
+        }
+
+        // Pre-order, so parents come before children.
+        let mut pids = vec![];
+        let mut stack = vec![pid];
+        while let Some(pid) = stack.pop() {
+            if !excluded.contains(&pid) {
+                pids.push(pid);
+            }
+            stack.extend(children.get(&pid).into_iter().flatten());
         }
+
+        pids.reverse();
+        pids
     }
 }
 
@@ -44,6 +209,12 @@ enum SearchFilterColumn {
     Name,
     Pid,
     Command,
+    /// Processes with a TCP/UDP socket bound to the local port.
+    Port,
+    /// Like `Port`, but only listening sockets.
+    Listen,
+    /// Processes using a file, anything inside a directory, or anything on a mount point.
+    File,
 }
 
 impl TryFrom<&str> for SearchFilterColumn {
@@ -55,19 +226,53 @@ impl TryFrom<&str> for SearchFilterColumn {
             "name" => Ok(SearchFilterColumn::Name),
             "pid" | "id" => Ok(SearchFilterColumn::Pid),
             "cmd" | "command" => Ok(SearchFilterColumn::Command),
-            other => Err(anyhow::format_err!("unknown column filter: '{other}'")),
+            "port" => Ok(SearchFilterColumn::Port),
+            "listen" => Ok(SearchFilterColumn::Listen),
+            "file" | "path" => Ok(SearchFilterColumn::File),
+            other => Err(anyhow::format_err!("unknown column '{other}'")),
         }
     }
 }
 
-// FUTUREWORK: Add SearchFilterType, adding/forcing specific search algos like:
-// Regex, Exact, Plain, Auto [current] (Exact for PID, otherwise Plain).
+/// Matching algorithm of a search filter, picked with a modifier before `:`.
+#[derive(Debug)]
+enum SearchFilterType {
+    /// No modifier, e.g. `name:bash`: exact match for PID, substring match otherwise.
+    Auto,
+    /// `name=:bash`
+    Exact,
+    /// `name~:^python[0-9.]*$`, case-insensitive.
+    Regex(Result<Regex, SearchError>),
+}
+
+/// Problem with the search text, which makes it impossible to filter as the user intended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchError {
+    /// Byte range of the offending part within the search text.
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+impl Display for SearchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
 #[derive(Debug)]
 struct SearchFilter {
     is_negative: bool,
-    column: anyhow::Result<SearchFilterColumn>,
+    column: Result<SearchFilterColumn, SearchError>,
+    typ: SearchFilterType,
     // NOTE: empty phrases are allowed, but will whole filter will be skipped in that case.
+    // Lowercased, unless the filter is a regex or a path.
     phrase: String,
+    /// Resolved `file:` path, unless the filter is a regex.
+    path: Option<PathQuery>,
+    /// Parsed `port:` and `listen:` number, unless the filter is a regex.
+    port: Option<u16>,
+    /// Alternatives, as in `name:firefox|name:chromium`. Any of them can match instead.
+    or: Vec<SearchFilter>,
 }
 
 impl Default for SearchFilter {
@@ -75,101 +280,576 @@ impl Default for SearchFilter {
         Self {
             is_negative: false,
             column: Ok(SearchFilterColumn::Any),
+            typ: SearchFilterType::Auto,
             phrase: String::new(),
+            path: None,
+            port: None,
+            or: vec![],
         }
     }
 }
 
-#[derive(Debug)]
-struct SearchFilters(Vec<SearchFilter>);
+impl SearchFilter {
+    /// Always false if the filter has errors.
+    fn is_match(&self, row: &Row, ctx: &SearchContext) -> bool {
+        let s = &self.phrase;
+        let Ok(column) = &self.column else {
+            return false;
+        };
 
-impl SearchFilters {
-    fn best_effort_parse_from_string(search_phrase: &str) -> Self {
-        let xs: Vec<_> = search_phrase
-            .split_ascii_whitespace()
-            .map(|mut search_word| {
-                let mut sf = SearchFilter::default();
-
-                if let Some(x) = search_word.strip_prefix("-") {
-                    search_word = x;
-                    sf.is_negative = true;
-                } else {
-                    sf.is_negative = false;
+        match &self.typ {
+            SearchFilterType::Auto => match column {
+                SearchFilterColumn::Any => {
+                    row.program_name_lowercase.contains(s)
+                        || row.command_lowercase.contains(s)
+                        || format!("{}", row.pid) == *s
                 }
-
-                if let Some((typ_str, x2)) = search_word.split_once(":") {
-                    // TODO: If this fails, maybe don't just return error and instead take a step
-                    // back and treat the whole thing like the ":" was part of search phrase?
-                    sf.column = SearchFilterColumn::try_from(typ_str);
-                    search_word = x2;
+                SearchFilterColumn::Command => row.command_lowercase.contains(s),
+                SearchFilterColumn::Name => row.program_name_lowercase.contains(s),
+                SearchFilterColumn::Pid => format!("{}", row.pid) == *s,
+                SearchFilterColumn::Port | SearchFilterColumn::Listen => {
+                    self.port_matches(row, ctx, |port| Some(port) == self.port)
+                }
+                SearchFilterColumn::File => self.file_matches(row, ctx, false),
+            },
+            SearchFilterType::Exact => match column {
+                SearchFilterColumn::Any => {
+                    row.program_name_lowercase == *s
+                        || row.command_lowercase == *s
+                        || format!("{}", row.pid) == *s
                 }
+                SearchFilterColumn::Command => row.command_lowercase == *s,
+                SearchFilterColumn::Name => row.program_name_lowercase == *s,
+                SearchFilterColumn::Pid => format!("{}", row.pid) == *s,
+                SearchFilterColumn::Port | SearchFilterColumn::Listen => {
+                    self.port_matches(row, ctx, |port| Some(port) == self.port)
+                }
+                SearchFilterColumn::File => self.file_matches(row, ctx, true),
+            },
+            SearchFilterType::Regex(Ok(re)) => match column {
+                SearchFilterColumn::Any => {
+                    re.is_match(&row.program_name)
+                        || re.is_match(&row.command)
+                        || re.is_match(&format!("{}", row.pid))
+                }
+                SearchFilterColumn::Command => re.is_match(&row.command),
+                SearchFilterColumn::Name => re.is_match(&row.program_name),
+                SearchFilterColumn::Pid => re.is_match(&format!("{}", row.pid)),
+                SearchFilterColumn::Port | SearchFilterColumn::Listen => {
+                    self.port_matches(row, ctx, |port| re.is_match(&port.to_string()))
+                }
+                SearchFilterColumn::File => ctx
+                    .files
+                    .iter()
+                    .flat_map(|files| files.paths(row.pid))
+                    .any(|used| re.is_match(&used.path.to_string_lossy())),
+            },
+            SearchFilterType::Regex(Err(_)) => false,
+        }
+    }
+
+    fn port_matches(&self, row: &Row, ctx: &SearchContext, f: impl Fn(u16) -> bool) -> bool {
+        let listening_only = matches!(self.column, Ok(SearchFilterColumn::Listen));
+        ctx.ports
+            .iter()
+            .flat_map(|ports| ports.ports(row.pid, listening_only))
+            .any(f)
+    }
 
-                sf.phrase = search_word.to_string();
+    fn file_matches(&self, row: &Row, ctx: &SearchContext, exact: bool) -> bool {
+        self.path.as_ref().is_some_and(|query| {
+            ctx.files
+                .iter()
+                .flat_map(|files| files.paths(row.pid))
+                .any(|used| query.matches(used, exact))
+        })
+    }
+
+    fn uses_ports(&self) -> bool {
+        matches!(
+            self.column,
+            Ok(SearchFilterColumn::Port | SearchFilterColumn::Listen)
+        )
+    }
+
+    fn uses_files(&self) -> bool {
+        matches!(self.column, Ok(SearchFilterColumn::File))
+    }
+
+    /// `None` if the filter should be skipped.
+    fn check(&self, row: &Row, ctx: &SearchContext) -> Option<bool> {
+        if self.phrase.is_empty() {
+            return None; // The whole filter doesn't make sense if the phrase is empty.
+        }
+
+        // Invalid filters match nothing, regardless of negation. The user is informed
+        // about them separately, see [`SearchQuery::errors`].
+        if self.errors().next().is_some() {
+            return Some(false);
+        }
+
+        // XOR inverts the result if filter.is_negative is true.
+        Some(self.is_match(row, ctx) ^ self.is_negative)
+    }
+
+    fn errors(&self) -> impl Iterator<Item = &SearchError> {
+        let regex_err = match &self.typ {
+            SearchFilterType::Regex(Err(e)) => Some(e),
+            _ => None,
+        };
+        self.column.as_ref().err().into_iter().chain(regex_err)
+    }
+}
+
+/// Characters that can be escaped with a backslash, in addition to whitespace. Backslashes before
+/// any other character are kept as they are, so that regexes like `\d+` don't need double escaping.
+const ESCAPABLE_CHARS: [char; 5] = ['\\', '"', '|', ':', '-'];
+
+/// Character of the search text, after resolving quotes and escapes.
+#[derive(Debug, Clone, Copy)]
+struct SearchChar {
+    /// Byte offset within the search text.
+    offset: usize,
+    c: char,
+    /// Quoted or escaped, so it has no special meaning.
+    is_literal: bool,
+}
+
+/// Splits search text into whitespace separated terms, each being a list of `|` separated
+/// alternatives. In regex values, e.g. `name~:^(a|b)$`, `|` is part of the regex instead.
+fn tokenize(search_phrase: &str) -> (Vec<Vec<Vec<SearchChar>>>, Vec<SearchError>) {
+    let mut terms = vec![];
+    let mut term = vec![];
+    let mut alternative = vec![];
+    let mut quote_start = None;
+
+    let mut chars = search_phrase.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        let literal = |(offset, c)| SearchChar {
+            offset,
+            c,
+            is_literal: true,
+        };
+        match c {
+            '\\' => match chars
+                .next_if(|(_, next)| ESCAPABLE_CHARS.contains(next) || next.is_ascii_whitespace())
+            {
+                Some(escaped) => alternative.push(literal(escaped)),
+                None => alternative.push(literal((offset, c))),
+            },
+            '"' => {
+                quote_start = match quote_start {
+                    Some(_) => None,
+                    None => Some(offset),
+                };
+            }
+            _ if quote_start.is_some() => alternative.push(literal((offset, c))),
+            _ if c.is_ascii_whitespace() => {
+                term.push(std::mem::take(&mut alternative));
+                terms.push(std::mem::take(&mut term));
+            }
+            '|' if !is_regex_value(&alternative) => term.push(std::mem::take(&mut alternative)),
+            _ => alternative.push(SearchChar {
+                offset,
+                c,
+                is_literal: false,
+            }),
+        }
+    }
+    term.push(alternative);
+    terms.push(term);
 
+    // Drop empty alternatives, so that `foo|` doesn't match everything.
+    for term in &mut terms {
+        term.retain(|alternative| !alternative.is_empty());
+    }
+    terms.retain(|term| !term.is_empty());
+
+    let errors = match quote_start {
+        Some(start) => vec![SearchError {
+            span: start..search_phrase.len(),
+            message: "unterminated quote".to_string(),
+        }],
+        None => vec![],
+    };
+
+    (terms, errors)
+}
+
+/// Whether the filter so far is a column with `~:`, so that the rest of it is a regex.
+fn is_regex_value(chars: &[SearchChar]) -> bool {
+    let is_special = |sc: &SearchChar, c: char| sc.c == c && !sc.is_literal;
+    match chars.iter().position(|sc| is_special(sc, ':')) {
+        Some(colon) => colon > 0 && is_special(&chars[colon - 1], '~'),
+        None => false,
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SearchFilters {
+    /// All of these have to match.
+    filters: Vec<SearchFilter>,
+    /// Errors that don't belong to any particular filter.
+    errors: Vec<SearchError>,
+}
+
+impl SearchFilters {
+    fn best_effort_parse_from_string(search_phrase: &str) -> Self {
+        let (terms, errors) = tokenize(search_phrase);
+        let filters = terms
+            .into_iter()
+            .map(|alternatives| {
+                let mut alternatives = alternatives.iter().map(|chars| Self::parse_filter(chars));
+                let mut sf = alternatives.next().expect("terms are never empty");
+                sf.or = alternatives.collect();
                 sf
             })
             .collect();
-        SearchFilters(xs)
+        SearchFilters { filters, errors }
     }
-}
 
-impl KillaData {
-    pub fn search(mut self, search_phrase: &str) -> Self {
-        let filters = SearchFilters::best_effort_parse_from_string(&search_phrase.to_lowercase());
+    fn parse_filter(mut chars: &[SearchChar]) -> SearchFilter {
+        let mut sf = SearchFilter::default();
+        let to_string = |chars: &[SearchChar]| chars.iter().map(|sc| sc.c).collect::<String>();
+        let span_of = |chars: &[SearchChar]| match (chars.first(), chars.last()) {
+            (Some(first), Some(last)) => first.offset..last.offset + last.c.len_utf8(),
+            _ => 0..0,
+        };
+        let is_special = |sc: &SearchChar, c: char| sc.c == c && !sc.is_literal;
 
-        self.rows.retain(|row| {
-            filters.0.iter().all(|filter| {
-                let s = &filter.phrase;
+        if let Some((first, rest)) = chars.split_first()
+            && is_special(first, '-')
+        {
+            chars = rest;
+            sf.is_negative = true;
+        } else {
+            sf.is_negative = false;
+        }
 
-                if s.is_empty() {
-                    return true; // The whole filter doesn't make sense if the phrase is empty.
+        let mut is_regex = false;
+        if let Some(colon) = chars.iter().position(|sc| is_special(sc, ':')) {
+            let mut typ_chars = &chars[..colon];
+            let column_span = span_of(typ_chars);
+            match typ_chars.split_last() {
+                Some((last, x)) if is_special(last, '~') => {
+                    typ_chars = x;
+                    is_regex = true;
                 }
+                Some((last, x)) if is_special(last, '=') => {
+                    typ_chars = x;
+                    sf.typ = SearchFilterType::Exact;
+                }
+                _ => {}
+            }
 
-                let is_match = match &filter.column {
-                    Ok(SearchFilterColumn::Any) => {
-                        row.program_name_lowercase.contains(s)
-                            || row.command_lowercase.contains(s)
-                            || format!("{}", row.pid) == *s
-                    }
-                    Ok(SearchFilterColumn::Command) => row.command_lowercase.contains(s),
-                    Ok(SearchFilterColumn::Name) => row.program_name_lowercase.contains(s),
-                    Ok(SearchFilterColumn::Pid) => format!("{}", row.pid) == *s,
+            // TODO: If this fails, maybe don't just return error and instead take a step
+            // back and treat the whole thing like the ":" was part of search phrase?
+            sf.column = match to_string(typ_chars).as_str() {
+                "" => Ok(SearchFilterColumn::Any),
+                typ_str => SearchFilterColumn::try_from(typ_str).map_err(|e| SearchError {
+                    span: column_span,
+                    message: e.to_string(),
+                }),
+            };
+            chars = &chars[colon + 1..];
+        }
+
+        let search_word = to_string(chars);
+        if is_regex {
+            sf.typ = SearchFilterType::Regex(
+                RegexBuilder::new(&search_word)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| SearchError {
+                        span: span_of(chars),
+                        message: match e {
+                            regex::Error::Syntax(_) => "invalid regex".to_string(),
+                            e => e.to_string(),
+                        },
+                    }),
+            );
+            sf.phrase = search_word;
+        } else {
+            if sf.uses_ports() && !search_word.is_empty() {
+                match search_word.parse::<u16>() {
+                    Ok(port) => sf.port = Some(port),
                     Err(_) => {
-                        // TODO: inform user that their input is has wrong syntax.
-                        // At least a red border around search box would be nice (but it might be
-                        // confused with plain "no results", so we also want to display "Syntax error").
-                        return false;
+                        sf.column = Err(SearchError {
+                            span: span_of(chars),
+                            message: "invalid port".to_string(),
+                        })
                     }
-                };
+                }
+            }
+            if sf.uses_files() && !search_word.is_empty() {
+                sf.path = Some(PathQuery::new(&search_word));
+                sf.phrase = search_word;
+            } else {
+                sf.phrase = search_word.to_lowercase();
+            }
+        }
 
-                // XOR inverts the result if filter.is_negative is true.
-                is_match ^ filter.is_negative
-            })
-        });
+        sf
+    }
 
-        self
+    fn errors(&self) -> impl Iterator<Item = &SearchError> {
+        let filter_errors = self.all_filters().flat_map(SearchFilter::errors);
+        self.errors.iter().chain(filter_errors)
     }
 
-    pub fn sort_by_column(&mut self, col: ColumnKind, order: SortOrder) -> &mut Self {
-        match col {
-            ColumnKind::Name => {}
-            ColumnKind::Memory => match order {
-                SortOrder::Ascending => self.rows.sort_by_key(|row| row.mem),
-                SortOrder::Descending => self.rows.sort_by_key(|row| u64::MAX - row.mem),
-            },
-            ColumnKind::Cpu => match order {
-                SortOrder::Ascending => self
-                    .rows
-                    .sort_by_key(|row| (row.cpu_perc * 10000.0f32) as u32 - (100 * 10000)),
-                SortOrder::Descending => self
-                    .rows
-                    .sort_by_key(|row| (100 * 10000) - (row.cpu_perc * 10000.0f32) as u32),
-            },
-            ColumnKind::Pid => self.rows.sort_by_key(|row| i32::MAX - row.pid),
-            ColumnKind::Command => {}
-            ColumnKind::CpuTime => {}
-            ColumnKind::Started => {}
+    fn all_filters(&self) -> impl Iterator<Item = &SearchFilter> {
+        self.filters
+            .iter()
+            .flat_map(|sf| std::iter::once(sf).chain(&sf.or))
+    }
+
+    fn check(&self, row: &Row, ctx: &SearchContext) -> bool {
+        self.filters.iter().all(|sf| {
+            let mut results = std::iter::once(sf)
+                .chain(&sf.or)
+                .filter_map(|filter| filter.check(row, ctx))
+                .peekable();
+            results.peek().is_none() || results.any(|is_match| is_match)
+        })
+    }
+}
+
+/// State of all processes needed by some filters. It's slow to get, so it's read only once
+/// some filter needs it, and kept for the same snapshot of processes.
+#[derive(Debug, Default)]
+struct SearchContext {
+    ports: Option<PortOwners>,
+    files: Option<FileUsers>,
+    /// Whether `files` have devices, for mount point queries.
+    files_with_devices: bool,
+}
+
+impl SearchContext {
+    /// Reads what `filters` need and isn't read yet.
+    fn prepare(&mut self, filters: &SearchFilters) {
+        if self.ports.is_none() && filters.all_filters().any(SearchFilter::uses_ports) {
+            self.ports = Some(PortOwners::read());
+        }
+        if filters.all_filters().any(SearchFilter::uses_files) {
+            let with_devices = filters.all_filters().any(|sf| {
+                sf.path
+                    .as_ref()
+                    .is_some_and(|path| path.mount_dev.is_some())
+            });
+            if self.files.is_none() || (with_devices && !self.files_with_devices) {
+                self.files = Some(FileUsers::read(with_devices));
+                self.files_with_devices = with_devices;
+            }
+        }
+    }
+}
+
+/// Prefix that switches search from the simple syntax to bottom's query language.
+pub const QUERY_PREFIX: char = '?';
+
+/// Parsed contents of the search box.
+///
+/// Kept around between refreshes, so that things like regexes are compiled only once.
+#[derive(Debug)]
+pub enum SearchQuery {
+    Simple(SearchFilters),
+    /// bottom's query language, e.g. `?mem > 1GB and (cpu > 5 or user = $USER)`.
+    /// `None` if the query is blank.
+    Bottom(Option<Result<ProcessQuery, SearchError>>),
+}
+
+impl Default for SearchQuery {
+    fn default() -> Self {
+        Self::Simple(SearchFilters::default())
+    }
+}
+
+impl SearchQuery {
+    pub fn parse(search_phrase: &str) -> Self {
+        let Some(query) = search_phrase.strip_prefix(QUERY_PREFIX) else {
+            return Self::Simple(SearchFilters::best_effort_parse_from_string(search_phrase));
         };
+
+        if query.trim().is_empty() {
+            return Self::Bottom(None);
+        }
+
+        let expanded_query = match std::env::var("USER") {
+            Ok(user) => query.replace("$USER", &user),
+            Err(_) => query.to_string(),
+        };
+
+        Self::Bottom(Some(
+            parse_query(&expanded_query, false, true, false).map_err(|e: QueryError| {
+                // bottom doesn't tell where exactly the error is.
+                SearchError {
+                    span: QUERY_PREFIX.len_utf8()..search_phrase.len(),
+                    message: e.to_string().to_lowercase(),
+                }
+            }),
+        ))
+    }
+
+    /// All syntax errors, in order of appearance.
+    pub fn errors(&self) -> Vec<&SearchError> {
+        match self {
+            SearchQuery::Simple(filters) => filters.errors().collect(),
+            SearchQuery::Bottom(Some(Err(e))) => vec![e],
+            SearchQuery::Bottom(_) => vec![],
+        }
+    }
+}
+
+impl KillaData {
+    pub fn search(mut self, query: &SearchQuery) -> Self {
+        match query {
+            SearchQuery::Simple(filters) => {
+                let mut ctx = self.search_context.lock().unwrap();
+                ctx.prepare(filters);
+                self.rows.retain(|row| filters.check(row, &ctx))
+            }
+            SearchQuery::Bottom(None) => {}
+            SearchQuery::Bottom(Some(Ok(query))) => self.rows.retain(|row| {
+                self.harvest
+                    .get(&row.pid)
+                    .is_some_and(|ps| query.check(ps, false))
+            }),
+            SearchQuery::Bottom(Some(Err(_))) => self.rows.clear(),
+        }
+
+        self
+    }
+
+    /// Sorts rows by the primary key, then the secondary one, then by pid. Equal rows keep
+    /// their order.
+    pub fn sort(&mut self, sort: &ProcessListSort) -> &mut Self {
+        self.rows.sort_by(|a, b| {
+            sort.order
+                .apply(compare_rows(a, b, sort.column))
+                .then_with(|| match sort.secondary {
+                    Some((column, order)) => order.apply(compare_rows(a, b, column)),
+                    None => Ordering::Equal,
+                })
+                .then_with(|| a.pid.cmp(&b.pid))
+        });
+        self
+    }
+}
+
+/// Compares rows by `column`, smallest first. Missing values are smaller than any other.
+fn compare_rows(a: &Row, b: &Row, column: ColumnKind) -> Ordering {
+    match column {
+        ColumnKind::Name => a.program_name_lowercase.cmp(&b.program_name_lowercase),
+        ColumnKind::Memory => a.mem.cmp(&b.mem),
+        ColumnKind::MemoryPercent => a.mem_perc.total_cmp(&b.mem_perc),
+        ColumnKind::Swap => a.swap.cmp(&b.swap),
+        // Can be over 100% when not normalized by the number of CPUs.
+        ColumnKind::Cpu => a.cpu_perc.total_cmp(&b.cpu_perc),
+        ColumnKind::Pid => a.pid.cmp(&b.pid),
+        ColumnKind::ParentPid => a.parent_pid.cmp(&b.parent_pid),
+        ColumnKind::User => a.user.cmp(&b.user),
+        ColumnKind::State => a.state_name.cmp(&b.state_name),
+        ColumnKind::Threads => a.threads.cmp(&b.threads),
+        ColumnKind::Nice => a.nice.cmp(&b.nice),
+        ColumnKind::IoPriority => a
+            .io_priority
+            .map(io_priority_rank)
+            .cmp(&b.io_priority.map(io_priority_rank)),
+        ColumnKind::Affinity => a
+            .affinity
+            .as_ref()
+            .map(CpuList::count)
+            .cmp(&b.affinity.as_ref().map(CpuList::count)),
+        ColumnKind::ReadRate => a.read_per_sec.cmp(&b.read_per_sec),
+        ColumnKind::WriteRate => a.write_per_sec.cmp(&b.write_per_sec),
+        ColumnKind::TotalRead => a.total_read.cmp(&b.total_read),
+        ColumnKind::TotalWrite => a.total_write.cmp(&b.total_write),
+        ColumnKind::Tty => a.tty.cmp(&b.tty),
+        ColumnKind::Command => a.command_lowercase.cmp(&b.command_lowercase),
+        ColumnKind::Started => a.started_at.cmp(&b.started_at),
+        ColumnKind::Age => a.age.cmp(&b.age),
+        ColumnKind::CpuTime => a.cpu_time.cmp(&b.cpu_time),
+    }
+}
+
+/// Orders I/O priorities from the most favored: realtime, best effort, idle, and by level
+/// within a class. Without a class, the kernel schedules the process as best effort.
+fn io_priority_rank(priority: IoPriority) -> (u8, u8) {
+    let class = match priority.class {
+        IoClass::Realtime => 0,
+        IoClass::BestEffort | IoClass::None => 1,
+        IoClass::Idle => 2,
+    };
+    (class, priority.level)
+}
+
+impl KillaData {
+    /// Reorders rows into a depth-first process tree, keeping the current order among siblings.
+    ///
+    /// Only rows that are present are considered, so a row whose parent was filtered out
+    /// becomes a root. Children of `collapsed_pids` are dropped.
+    pub fn into_tree(mut self, collapsed_pids: &HashSet<i32>) -> Self {
+        let present: HashSet<i32> = self.rows.iter().map(|row| row.pid).collect();
+
+        let mut roots = vec![];
+        let mut children: HashMap<i32, Vec<usize>> = HashMap::new();
+        for (idx, row) in self.rows.iter().enumerate() {
+            match row.parent_pid {
+                Some(ppid) if ppid != row.pid && present.contains(&ppid) => {
+                    children.entry(ppid).or_default().push(idx)
+                }
+                _ => roots.push(idx),
+            }
+        }
+
+        let mut slots: Vec<Option<Row>> = self.rows.drain(..).map(Some).collect();
+        let mut out = Vec::with_capacity(slots.len());
+
+        // (row index, is last among siblings, depth)
+        let mut stack: Vec<(usize, bool, usize)> = roots
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, idx)| (*idx, i == 0, 0))
+            .collect();
+        // For each ancestor depth, whether that ancestor was the last of its siblings.
+        let mut last_at_depth: Vec<bool> = vec![];
+
+        while let Some((idx, is_last, depth)) = stack.pop() {
+            let Some(mut row) = slots[idx].take() else {
+                continue;
+            };
+            last_at_depth.truncate(depth);
+
+            let mut prefix = String::new();
+            if depth > 0 {
+                for ancestor_is_last in &last_at_depth[1..] {
+                    prefix.push_str(if *ancestor_is_last { "   " } else { "│  " });
+                }
+                prefix.push_str(if is_last { "└─ " } else { "├─ " });
+            }
+            last_at_depth.push(is_last);
+
+            let kids = children.get(&row.pid);
+            let is_collapsed = collapsed_pids.contains(&row.pid);
+            if let (Some(kids), false) = (kids, is_collapsed) {
+                stack.extend(
+                    kids.iter()
+                        .rev()
+                        .enumerate()
+                        .map(|(i, kid)| (*kid, i == 0, depth + 1)),
+                );
+            }
+
+            row.tree = Some(TreeNode {
+                prefix,
+                has_children: kids.is_some(),
+                is_collapsed,
+            });
+            out.push(row);
+        }
+
+        self.rows = out;
         self
     }
 }
@@ -180,17 +860,111 @@ impl From<KillaData> for Vec<Row> {
     }
 }
 
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SortOrder {
     Ascending,
     #[default]
     Descending,
 }
 
-#[derive(Clone, Copy)]
+impl SortOrder {
+    /// Order a column gets when first sorted by: text A to Z, numbers largest first.
+    pub fn initial(column: ColumnKind) -> Self {
+        match column {
+            ColumnKind::Name
+            | ColumnKind::User
+            | ColumnKind::State
+            | ColumnKind::Tty
+            | ColumnKind::Command => SortOrder::Ascending,
+            _ => SortOrder::Descending,
+        }
+    }
+
+    pub fn reversed(self) -> Self {
+        match self {
+            SortOrder::Ascending => SortOrder::Descending,
+            SortOrder::Descending => SortOrder::Ascending,
+        }
+    }
+
+    fn apply(self, ordering: Ordering) -> Ordering {
+        match self {
+            SortOrder::Ascending => ordering,
+            SortOrder::Descending => ordering.reverse(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct ProcessListSort {
     pub(crate) column: ColumnKind,
     pub(crate) order: SortOrder,
+    /// Key for rows equal in `column`, e.g. memory among idle processes.
+    pub(crate) secondary: Option<(ColumnKind, SortOrder)>,
+}
+
+impl ProcessListSort {
+    pub fn new(column: ColumnKind, order: SortOrder) -> Self {
+        Self {
+            column,
+            order,
+            secondary: None,
+        }
+    }
+
+    /// Sorts by `column`, or reverses the order if already sorted by it.
+    ///
+    /// With `as_secondary`, `column` becomes the secondary key instead. Choosing the secondary
+    /// key again reverses it, and then removes it.
+    pub fn select(&mut self, column: ColumnKind, as_secondary: bool) {
+        if as_secondary {
+            if column == self.column {
+                return;
+            }
+            self.secondary = match self.secondary {
+                Some((current, order)) if current == column => {
+                    (order == SortOrder::initial(column)).then(|| (column, order.reversed()))
+                }
+                _ => Some((column, SortOrder::initial(column))),
+            };
+        } else if column == self.column {
+            self.order = self.order.reversed();
+        } else {
+            if self
+                .secondary
+                .is_some_and(|(secondary, _)| secondary == column)
+            {
+                self.secondary = None;
+            }
+            self.column = column;
+            self.order = SortOrder::initial(column);
+        }
+    }
+
+    /// Arrow shown next to the header of `column`, hollow for the secondary key.
+    pub fn indicator(&self, column: ColumnKind) -> &'static str {
+        if column == self.column {
+            return match self.order {
+                SortOrder::Ascending => " ▲",
+                SortOrder::Descending => " ▼",
+            };
+        }
+        match self.secondary {
+            Some((secondary, SortOrder::Ascending)) if secondary == column => " △",
+            Some((secondary, SortOrder::Descending)) if secondary == column => " ▽",
+            _ => "",
+        }
+    }
+}
+
+impl Display for ProcessListSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.column, self.indicator(self.column))?;
+        if let Some((secondary, _)) = self.secondary {
+            write!(f, ", then {secondary}{}", self.indicator(secondary))?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -202,7 +976,7 @@ mod tests {
     #[test]
     fn test_searchfilters_parse_pid() {
         let case = "pid:123";
-        let sf = SearchFilters::best_effort_parse_from_string(case).0;
+        let sf = SearchFilters::best_effort_parse_from_string(case).filters;
         assert_eq!(sf.len(), 1);
         assert!(!sf[0].is_negative);
         assert!(matches!(sf[0].column, Ok(SearchFilterColumn::Pid)));
@@ -212,7 +986,7 @@ mod tests {
     #[test]
     fn test_searchfilters_parse_negative_name() {
         let case = "-name:killa";
-        let sf = SearchFilters::best_effort_parse_from_string(case).0;
+        let sf = SearchFilters::best_effort_parse_from_string(case).filters;
         assert_eq!(sf.len(), 1);
         assert!(sf[0].is_negative);
         assert!(matches!(sf[0].column, Ok(SearchFilterColumn::Name)));
@@ -222,7 +996,7 @@ mod tests {
     #[test]
     fn test_searchfilters_parse_many() {
         let case = "cmd:cargo name:rust";
-        let sf = SearchFilters::best_effort_parse_from_string(case).0;
+        let sf = SearchFilters::best_effort_parse_from_string(case).filters;
 
         assert_eq!(sf.len(), 2);
 
@@ -238,7 +1012,7 @@ mod tests {
     #[test]
     fn test_searchfilters_parse_unknown_column() {
         let case = "-unknown:killa";
-        let sf = SearchFilters::best_effort_parse_from_string(case).0;
+        let sf = SearchFilters::best_effort_parse_from_string(case).filters;
         assert_eq!(sf.len(), 1);
         assert!(sf[0].is_negative);
         assert!(sf[0].column.is_err());
@@ -246,52 +1020,511 @@ mod tests {
     }
 
     #[test]
-    fn test_search() {
-        let data = KillaData {
+    fn test_searchfilters_parse_quoted() {
+        let case = r#"cmd:"--type=renderer --lang" "name with spaces""#;
+        let sf = SearchFilters::best_effort_parse_from_string(case).filters;
+        assert_eq!(sf.len(), 2);
+        assert!(matches!(sf[0].column, Ok(SearchFilterColumn::Command)));
+        assert_eq!(sf[0].phrase, "--type=renderer --lang");
+        assert!(matches!(sf[1].column, Ok(SearchFilterColumn::Any)));
+        assert_eq!(sf[1].phrase, "name with spaces");
+    }
+
+    #[test]
+    fn test_searchfilters_parse_quoted_special_chars() {
+        let case = r#""-a:b|c" name:"x""#;
+        let sf = SearchFilters::best_effort_parse_from_string(case).filters;
+        assert_eq!(sf.len(), 2);
+        assert!(!sf[0].is_negative);
+        assert!(matches!(sf[0].column, Ok(SearchFilterColumn::Any)));
+        assert_eq!(sf[0].phrase, "-a:b|c");
+        assert!(sf[0].or.is_empty());
+        assert!(matches!(sf[1].column, Ok(SearchFilterColumn::Name)));
+        assert_eq!(sf[1].phrase, "x");
+    }
+
+    #[test]
+    fn test_searchfilters_parse_escapes() {
+        let case = r#"\-foo\ bar a\:b \"q\" \\ name~:\d+\|x"#;
+        let sf = SearchFilters::best_effort_parse_from_string(case).filters;
+        let phrases: Vec<_> = sf.iter().map(|sf| sf.phrase.as_str()).collect();
+        assert_eq!(phrases, vec!["-foo bar", "a:b", "\"q\"", "\\", "\\d+|x"]);
+        assert!(!sf[0].is_negative);
+        assert!(matches!(sf[1].column, Ok(SearchFilterColumn::Any)));
+        assert!(matches!(sf[4].typ, SearchFilterType::Regex(Ok(_))));
+    }
+
+    #[test]
+    fn test_searchfilters_parse_or() {
+        let case = "name:firefox|-name:chromium| cmd:x";
+        let sf = SearchFilters::best_effort_parse_from_string(case).filters;
+        assert_eq!(sf.len(), 2);
+        assert!(matches!(sf[0].column, Ok(SearchFilterColumn::Name)));
+        assert_eq!(sf[0].phrase, "firefox");
+        assert_eq!(sf[0].or.len(), 1);
+        assert!(sf[0].or[0].is_negative);
+        assert_eq!(sf[0].or[0].phrase, "chromium");
+        assert!(sf[1].or.is_empty());
+    }
+
+    #[test]
+    fn test_search_or() {
+        assert_eq!(names("name:init|name:firefox"), vec!["init", "firefox"]);
+        assert_eq!(names("name:init|name:firefox -pid:1"), vec!["firefox"]);
+        assert_eq!(names("name:init|"), vec!["init"]);
+        assert_eq!(names(r#"cmd:"firefox --flag1""#), vec!["firefox"]);
+        assert_eq!(names(r#"cmd:firefox\ --flag1"#), vec!["firefox"]);
+    }
+
+    #[test]
+    fn test_search_errors() {
+        let case = "firefox -nmae:foo  name~:( pid:1";
+        let query = SearchQuery::parse(case);
+        let errors = query.errors();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].message, "unknown column 'nmae'");
+        assert_eq!(&case[errors[0].span.clone()], "nmae");
+        assert_eq!(errors[1].message, "invalid regex");
+        assert_eq!(&case[errors[1].span.clone()], "(");
+
+        assert!(
+            SearchQuery::parse("-name:foo name=:bar")
+                .errors()
+                .is_empty()
+        );
+        assert_eq!(SearchQuery::parse("?(foo").errors()[0].span, 1..5);
+        assert!(SearchQuery::parse("?").errors().is_empty());
+        assert_eq!(
+            SearchQuery::parse("port:http").errors()[0].message,
+            "invalid port"
+        );
+        assert!(
+            SearchQuery::parse("listen:3000 port~:^80")
+                .errors()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_search_port_numerically() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut data = test_data();
+        data.rows[1].pid = std::process::id() as i32;
+
+        for query in [
+            format!("port:{port}"),
+            format!("port:0{port}"),
+            format!("listen=:+{port}"),
+            format!("port~:^{port}$"),
+        ] {
+            assert_eq!(names_in(&data, &query), ["killa"], "{query}");
+        }
+    }
+
+    #[test]
+    fn test_searchfilters_parse_file() {
+        let sf = SearchFilters::best_effort_parse_from_string("file:/Some/Path").filters;
+        assert!(matches!(sf[0].column, Ok(SearchFilterColumn::File)));
+        assert_eq!(sf[0].phrase, "/Some/Path");
+        let path = &sf[0].path.as_ref().unwrap().path;
+        assert_eq!(path, std::path::Path::new("/Some/Path"));
+    }
+
+    #[test]
+    fn test_searchfilters_parse_regex() {
+        let case = "name~:^Python[0-9.]*$";
+        let sf = SearchFilters::best_effort_parse_from_string(case).filters;
+        assert_eq!(sf.len(), 1);
+        assert!(matches!(sf[0].column, Ok(SearchFilterColumn::Name)));
+        assert!(matches!(sf[0].typ, SearchFilterType::Regex(Ok(_))));
+        assert_eq!(sf[0].phrase, "^Python[0-9.]*$");
+    }
+
+    #[test]
+    fn test_searchfilters_parse_invalid_regex() {
+        let case = "~:(";
+        let sf = SearchFilters::best_effort_parse_from_string(case).filters;
+        assert_eq!(sf.len(), 1);
+        assert!(matches!(sf[0].column, Ok(SearchFilterColumn::Any)));
+        assert!(matches!(sf[0].typ, SearchFilterType::Regex(Err(_))));
+    }
+
+    #[test]
+    fn test_searchfilters_parse_exact() {
+        let case = "-name=:Bash";
+        let sf = SearchFilters::best_effort_parse_from_string(case).filters;
+        assert_eq!(sf.len(), 1);
+        assert!(sf[0].is_negative);
+        assert!(matches!(sf[0].column, Ok(SearchFilterColumn::Name)));
+        assert!(matches!(sf[0].typ, SearchFilterType::Exact));
+        assert_eq!(sf[0].phrase, "bash");
+    }
+
+    #[test]
+    fn test_search_regex_and_exact() {
+        assert_eq!(names("name~:^(init|FIREFOX)$"), vec!["init", "firefox"]);
+        assert_eq!(names("name~:\"^(init|FIREFOX)$\""), vec!["init", "firefox"]);
+        assert_eq!(names("pid:1|name~:^kil"), vec!["init", "killa"]);
+        assert_eq!(names("cmd~:--flag[0-9]"), vec!["firefox"]);
+        assert_eq!(names("name=:kill"), Vec::<String>::new());
+        assert_eq!(names("name=:killa"), vec!["killa"]);
+        assert_eq!(names("-cmd=:init"), vec!["killa", "firefox"]);
+        assert!(names("name~:(").is_empty());
+    }
+
+    /// Row with names and other fields that are the same for all test rows filled in.
+    fn test_row(pid: i32, name: &str, command: &str) -> Row {
+        Row {
+            row_index: pid as usize - 1,
+            program_name: name.to_string(),
+            program_name_lowercase: name.to_lowercase(),
+            mem_perc: 1.0,
+            pid,
+            command: command.to_string(),
+            command_lowercase: command.to_lowercase(),
+            user: "root".to_string(),
+            threads: 1,
+            state_name: "Sleeping".to_string(),
+            state: 'S',
+            start_ticks: 100,
+            ..Default::default()
+        }
+    }
+
+    fn test_data() -> KillaData {
+        let mut data = KillaData {
             rows: vec![
                 Row {
-                    row_index: 0,
-                    program_name: "init".to_string(),
-                    program_name_lowercase: "init".to_string(),
                     mem: 100_000,
                     cpu_perc: 0.01,
-                    pid: 1,
-                    command: "init".to_string(),
-                    command_lowercase: "init".to_string(),
-                    cpu_time: Duration::from_secs(20),
+                    age: Duration::from_secs(20),
+                    cpu_time: Duration::from_secs(3),
+                    parent_pid: Some(0),
+                    ..test_row(1, "init", "init")
                 },
                 Row {
-                    row_index: 1,
-                    program_name: "killa".to_string(),
-                    program_name_lowercase: "killa".to_string(),
                     mem: 300_000,
                     cpu_perc: 2.22,
-                    pid: 2,
-                    command: "/nix/store/xxxxxxxxxxxx-killa".to_string(),
-                    command_lowercase: "/nix/store/xxxxxxxxxxxx-killa".to_string(),
-                    cpu_time: Duration::from_secs(10),
+                    age: Duration::from_secs(10),
+                    cpu_time: Duration::from_secs(40),
+                    parent_pid: Some(1),
+                    ..test_row(2, "killa", "/nix/store/xxxxxxxxxxxx-killa")
                 },
                 Row {
-                    row_index: 2,
-                    program_name: "firefox".to_string(),
-                    program_name_lowercase: "firefox".to_string(),
                     mem: 100_000_000,
                     cpu_perc: 10.00,
-                    pid: 3,
-                    command: "firefox --flag1".to_string(),
-                    command_lowercase: "firefox --flag1".to_string(),
-                    cpu_time: Duration::from_secs(100),
+                    age: Duration::from_secs(100),
+                    cpu_time: Duration::from_secs(7),
+                    parent_pid: Some(1),
+                    ..test_row(3, "firefox", "firefox --flag1")
                 },
             ],
+            harvest: Default::default(),
             memory: MemHarvest {
                 used_bytes: 50,
                 total_bytes: 100,
             },
+            search_context: Default::default(),
         };
+        data.harvest = Arc::new(
+            data.rows
+                .iter()
+                .map(|row| {
+                    let ps = ProcessHarvest {
+                        pid: row.pid,
+                        parent_pid: row.parent_pid,
+                        cpu_usage_percent: row.cpu_perc,
+                        mem_usage_bytes: row.mem * 1_000_000,
+                        name: row.program_name.clone(),
+                        command: row.command.clone(),
+                        time: row.age,
+                        cpu_time: row.cpu_time,
+                        process_state: (row.state_name.clone(), row.state),
+                        nice: row.nice,
+                        start_time: row.start_ticks,
+                        user: "root".into(),
+                        ..Default::default()
+                    };
+                    (row.pid, ps)
+                })
+                .collect(),
+        );
+        data
+    }
+
+    /// Names of test rows that match `query`.
+    fn names(query: &str) -> Vec<String> {
+        names_in(&test_data(), query)
+    }
+
+    fn names_in(data: &KillaData, query: &str) -> Vec<String> {
+        let rows: Vec<Row> = data.clone().search(&SearchQuery::parse(query)).into();
+        rows.into_iter().map(|row| row.program_name).collect()
+    }
+
+    #[test]
+    fn test_search() {
+        let data = test_data();
         let s = "-pid:1 name:killa";
-        let result = KillaData::search(data.clone(), s);
+        let result = KillaData::search(data.clone(), &SearchQuery::parse(s));
 
         assert_eq!(result.rows.len(), 1);
         assert_eq!(result.rows[0].program_name, "killa");
     }
+
+    #[test]
+    fn test_query() {
+        assert_eq!(names("?memb > 200GB"), vec!["killa", "firefox"]);
+        assert_eq!(names("?mem > 200GB"), vec!["killa", "firefox"]);
+        assert_eq!(names("?cpu > 5 or init"), vec!["init", "firefox"]);
+        assert_eq!(names("?(killa or firefox) and cpu < 5"), vec!["killa"]);
+        assert_eq!(names("?"), vec!["init", "killa", "firefox"]);
+        assert!(names("?(killa").is_empty());
+    }
+
+    #[test]
+    fn test_contains_checks_start_time() {
+        let data = test_data();
+        assert!(data.contains(&ProcessKey {
+            pid: 2,
+            start_time: 100
+        }));
+        // Same pid, but a different process.
+        assert!(!data.contains(&ProcessKey {
+            pid: 2,
+            start_time: 101
+        }));
+        assert!(!data.contains(&ProcessKey {
+            pid: 4,
+            start_time: 100
+        }));
+    }
+
+    #[test]
+    fn test_process_key_identity() {
+        let pid = std::process::id() as i32;
+        let start_time = procfs::process::Process::myself()
+            .unwrap()
+            .stat()
+            .unwrap()
+            .starttime;
+
+        let key = ProcessKey { pid, start_time };
+        assert!(key.is_same_process());
+        assert!(key.is_running());
+
+        // Same pid, but a different process.
+        let reused = ProcessKey {
+            pid,
+            start_time: start_time + 1,
+        };
+        assert!(!reused.is_same_process());
+        assert!(!reused.is_running());
+    }
+
+    #[test]
+    fn test_subtree_children_first() {
+        let mut data = test_data();
+        let mut harvest = (*data.harvest).clone();
+        harvest.insert(
+            4,
+            ProcessHarvest {
+                pid: 4,
+                parent_pid: Some(2),
+                ..Default::default()
+            },
+        );
+        data.harvest = Arc::new(harvest);
+
+        let pids = data.subtree(1, None);
+        assert_eq!(pids.len(), 4);
+        assert_eq!(pids.last(), Some(&1));
+        let pos = |pid| pids.iter().position(|x| *x == pid).unwrap();
+        assert!(pos(4) < pos(2));
+
+        assert_eq!(data.subtree(2, None), vec![4, 2]);
+        assert_eq!(data.subtree(3, None), vec![3]);
+        assert!(data.subtree(5, None).is_empty());
+    }
+
+    #[test]
+    fn test_subtree_excludes_pid_and_ancestors() {
+        let data = test_data();
+        // 3 is a child of 1, like killa running in a terminal.
+        assert_eq!(data.subtree(1, Some(3)), vec![2]);
+        assert_eq!(data.subtree(2, Some(3)), vec![2]);
+        assert!(data.subtree(3, Some(3)).is_empty());
+        assert_eq!(data.subtree(1, Some(5)).len(), 3);
+    }
+
+    #[test]
+    fn test_read_columns_only_when_shown() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("60")
+            .spawn()
+            .unwrap();
+        let mut data = test_data();
+        data.rows[0].pid = child.id() as i32;
+
+        data.read_columns(&[Column::new(ColumnKind::Name)]);
+        assert!(data.rows[0].affinity.is_none());
+
+        data.read_columns(&[Column::new(ColumnKind::Affinity)]);
+        assert!(data.rows[0].affinity.is_some());
+        assert!(data.rows[0].io_priority.is_none());
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_stopped_rows() {
+        let mut data = test_data();
+        assert!(!data.rows[0].is_stopped());
+        data.rows[0].state = 'T';
+        assert!(data.rows[0].is_stopped());
+    }
+
+    #[test]
+    fn test_sort_by_started() {
+        let mut data = test_data();
+        let now = Local::now();
+        data.rows[0].started_at = Some(now - chrono::TimeDelta::hours(3));
+        data.rows[2].started_at = Some(now);
+        let pids = |data: &KillaData| data.rows.iter().map(|row| row.pid).collect::<Vec<_>>();
+
+        data.sort(&ProcessListSort::new(
+            ColumnKind::Started,
+            SortOrder::Descending,
+        ));
+        assert_eq!(pids(&data), vec![3, 1, 2]);
+        data.sort(&ProcessListSort::new(
+            ColumnKind::Started,
+            SortOrder::Ascending,
+        ));
+        assert_eq!(pids(&data), vec![2, 1, 3]);
+    }
+
+    #[test]
+    fn test_sort_by_cpu_time_and_age() {
+        let mut data = test_data();
+        let pids = |data: &KillaData| data.rows.iter().map(|row| row.pid).collect::<Vec<_>>();
+
+        // killa (2) is the youngest, but burned the most CPU.
+        data.sort(&ProcessListSort::new(
+            ColumnKind::CpuTime,
+            SortOrder::Descending,
+        ));
+        assert_eq!(pids(&data), vec![2, 3, 1]);
+        data.sort(&ProcessListSort::new(
+            ColumnKind::Age,
+            SortOrder::Descending,
+        ));
+        assert_eq!(pids(&data), vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_sort_order_and_tiebreak() {
+        let mut data = test_data();
+        // Unnormalized CPU usage goes over 100%, and used to underflow.
+        data.rows[0].cpu_perc = 350.0;
+        let pids = |data: &KillaData| data.rows.iter().map(|row| row.pid).collect::<Vec<_>>();
+
+        data.sort(&ProcessListSort::new(
+            ColumnKind::Pid,
+            SortOrder::Descending,
+        ));
+        assert_eq!(pids(&data), vec![3, 2, 1]);
+        data.sort(&ProcessListSort::new(
+            ColumnKind::Name,
+            SortOrder::Ascending,
+        ));
+        assert_eq!(pids(&data), vec![3, 1, 2]);
+
+        data.sort(&ProcessListSort::new(
+            ColumnKind::Cpu,
+            SortOrder::Descending,
+        ));
+        assert_eq!(pids(&data), vec![1, 3, 2]);
+        data.sort(&ProcessListSort::new(ColumnKind::Cpu, SortOrder::Ascending));
+        assert_eq!(pids(&data), vec![2, 3, 1]);
+
+        // Equal in both directions, so ordered by pid.
+        data.sort(&ProcessListSort::new(
+            ColumnKind::User,
+            SortOrder::Descending,
+        ));
+        assert_eq!(pids(&data), vec![1, 2, 3]);
+
+        let mut sort = ProcessListSort::new(ColumnKind::MemoryPercent, SortOrder::Descending);
+        sort.secondary = Some((ColumnKind::Memory, SortOrder::Descending));
+        data.sort(&sort);
+        assert_eq!(pids(&data), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_select_sort_column() {
+        let mut sort = ProcessListSort::new(ColumnKind::Cpu, SortOrder::Descending);
+        sort.select(ColumnKind::Cpu, false);
+        assert_eq!(sort.order, SortOrder::Ascending);
+        sort.select(ColumnKind::Name, false);
+        assert_eq!(
+            sort,
+            ProcessListSort::new(ColumnKind::Name, SortOrder::Ascending)
+        );
+
+        sort.select(ColumnKind::Memory, true);
+        assert_eq!(
+            sort.secondary,
+            Some((ColumnKind::Memory, SortOrder::Descending))
+        );
+        assert_eq!(sort.to_string(), "Name ▲, then Memory ▽");
+        sort.select(ColumnKind::Memory, true);
+        assert_eq!(
+            sort.secondary,
+            Some((ColumnKind::Memory, SortOrder::Ascending))
+        );
+        sort.select(ColumnKind::Memory, true);
+        assert_eq!(sort.secondary, None);
+
+        // The secondary key is dropped when it becomes the primary one.
+        sort.select(ColumnKind::Memory, true);
+        sort.select(ColumnKind::Name, true);
+        sort.select(ColumnKind::Memory, false);
+        assert_eq!(
+            sort,
+            ProcessListSort::new(ColumnKind::Memory, SortOrder::Descending)
+        );
+    }
+
+    #[test]
+    fn test_tree() {
+        let tree: Vec<Row> = test_data().into_tree(&HashSet::new()).into();
+        let layout: Vec<_> = tree
+            .iter()
+            .map(|row| (row.pid, row.tree.as_ref().unwrap().prefix.as_str()))
+            .collect();
+        assert_eq!(layout, vec![(1, ""), (2, "├─ "), (3, "└─ ")]);
+        assert!(tree[0].tree.as_ref().unwrap().has_children);
+        assert!(!tree[1].tree.as_ref().unwrap().has_children);
+    }
+
+    #[test]
+    fn test_tree_collapsed() {
+        let tree: Vec<Row> = test_data().into_tree(&HashSet::from([1])).into();
+        assert_eq!(tree.len(), 1);
+        assert!(tree[0].tree.as_ref().unwrap().is_collapsed);
+    }
+
+    #[test]
+    fn test_tree_filtered_parent_becomes_root() {
+        let data = test_data().search(&SearchQuery::parse("-pid:1"));
+        let tree: Vec<Row> = data.into_tree(&HashSet::new()).into();
+        assert_eq!(tree.len(), 2);
+        assert!(
+            tree.iter()
+                .all(|row| row.tree.as_ref().unwrap().prefix.is_empty())
+        );
+    }
 }
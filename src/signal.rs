@@ -0,0 +1,360 @@
+use crate::process_data::ProcessKey;
+use rustix::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd};
+use rustix::process::{Pid, PidfdFlags, Signal, kill_process, pidfd_open, pidfd_send_signal};
+use std::fmt;
+use std::sync::Arc;
+
+/// Named signals offered by the signal picker, in order.
+const NAMED_SIGNALS: [(Signal, &str); 13] = [
+    (Signal::Hup, "SIGHUP"),
+    (Signal::Int, "SIGINT"),
+    (Signal::Quit, "SIGQUIT"),
+    (Signal::Abort, "SIGABRT"),
+    (Signal::Kill, "SIGKILL"),
+    (Signal::Usr1, "SIGUSR1"),
+    (Signal::Usr2, "SIGUSR2"),
+    (Signal::Alarm, "SIGALRM"),
+    (Signal::Term, "SIGTERM"),
+    (Signal::Cont, "SIGCONT"),
+    (Signal::Stop, "SIGSTOP"),
+    (Signal::Tstp, "SIGTSTP"),
+    (Signal::Winch, "SIGWINCH"),
+];
+
+/// Signal that can be sent to a process.
+///
+/// [`Signal`] doesn't cover realtime signals, so these are stored separately.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KillSignal {
+    Named(Signal),
+    /// `SIGRTMIN+n`
+    Realtime(i32),
+}
+
+impl Default for KillSignal {
+    fn default() -> Self {
+        Self::Named(Signal::Term)
+    }
+}
+
+impl KillSignal {
+    /// All signals offered by the signal picker.
+    pub fn all() -> Vec<KillSignal> {
+        let named = NAMED_SIGNALS.iter().map(|(sig, _)| Self::Named(*sig));
+        let realtime = (0..=libc::SIGRTMAX() - libc::SIGRTMIN()).map(Self::Realtime);
+        named.chain(realtime).collect()
+    }
+
+    /// `Some(true)` if the signal pauses processes, `Some(false)` if it resumes them.
+    pub fn pauses(self) -> Option<bool> {
+        match self {
+            Self::Named(Signal::Stop) => Some(true),
+            Self::Named(Signal::Cont) => Some(false),
+            _ => None,
+        }
+    }
+
+    pub fn raw(self) -> i32 {
+        match self {
+            Self::Named(sig) => sig as i32,
+            Self::Realtime(n) => libc::SIGRTMIN() + n,
+        }
+    }
+
+    pub fn send(self, pid: i32) -> std::io::Result<()> {
+        match self {
+            Self::Named(sig) => {
+                let pid = Pid::from_raw(pid).expect("pid in table should be valid");
+                Ok(kill_process(pid, sig)?)
+            }
+            Self::Realtime(_) => {
+                // SAFETY: kill has no memory safety requirements.
+                match unsafe { libc::kill(pid, self.raw()) } {
+                    0 => Ok(()),
+                    _ => Err(std::io::Error::last_os_error()),
+                }
+            }
+        }
+    }
+}
+
+impl KillSignal {
+    pub fn send_pidfd(self, pidfd: BorrowedFd<'_>) -> std::io::Result<()> {
+        match self {
+            Self::Named(sig) => Ok(pidfd_send_signal(pidfd, sig)?),
+            Self::Realtime(_) => {
+                // SAFETY: pidfd is a valid file descriptor, and info is allowed to be null.
+                let ret = unsafe {
+                    libc::syscall(
+                        libc::SYS_pidfd_send_signal,
+                        pidfd.as_raw_fd(),
+                        self.raw(),
+                        std::ptr::null::<libc::siginfo_t>(),
+                        0,
+                    )
+                };
+                match ret {
+                    0 => Ok(()),
+                    _ => Err(std::io::Error::last_os_error()),
+                }
+            }
+        }
+    }
+}
+
+/// Process to be signalled, captured when the signal is staged.
+///
+/// Holds a pidfd where supported, so that the signal can't reach an unrelated process that reused
+/// the pid in the meantime. Otherwise, the start time is checked again right before signalling.
+#[derive(Clone, Debug)]
+pub struct ProcessHandle {
+    pub key: ProcessKey,
+    pub name: String,
+    pidfd: Option<Arc<OwnedFd>>,
+    /// The pid already belonged to a different process when the handle was opened.
+    is_gone: bool,
+}
+
+impl ProcessHandle {
+    pub fn open(key: ProcessKey, name: String) -> Self {
+        let pidfd = Pid::from_raw(key.pid)
+            .and_then(|pid| pidfd_open(pid, PidfdFlags::empty()).ok())
+            .map(Arc::new);
+        // Checked after opening the pidfd, so that the pidfd is known to refer to this process.
+        let is_gone = !key.is_same_process();
+        Self {
+            key,
+            name,
+            pidfd,
+            is_gone,
+        }
+    }
+
+    pub fn send(&self, sig: KillSignal) -> SignalOutcome {
+        match &self.pidfd {
+            _ if self.is_gone => SignalOutcome::AlreadyGone,
+            Some(pidfd) => sig.send_pidfd(pidfd.as_fd()).into(),
+            None => self.apply(|pid| sig.send(pid)),
+        }
+    }
+
+    /// Runs `f` with the pid, if it still belongs to the same process.
+    ///
+    /// Unlike with signals, there's no pidfd variant of most syscalls, so this is racy in theory,
+    /// but the window is tiny compared to the time the user takes to confirm.
+    pub fn apply(&self, f: impl FnOnce(i32) -> std::io::Result<()>) -> SignalOutcome {
+        if self.is_gone || !self.key.is_same_process() {
+            return SignalOutcome::AlreadyGone;
+        }
+        f(self.key.pid).into()
+    }
+}
+
+impl From<Signal> for KillSignal {
+    fn from(sig: Signal) -> Self {
+        Self::Named(sig)
+    }
+}
+
+impl fmt::Display for KillSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Named(sig) => match NAMED_SIGNALS.iter().find(|(s, _)| s == sig) {
+                Some((_, name)) => write!(f, "{name}"),
+                None => write!(f, "SIG{}", format!("{sig:?}").to_uppercase()),
+            },
+            Self::Realtime(0) => write!(f, "SIGRTMIN"),
+            Self::Realtime(_) if self.raw() == libc::SIGRTMAX() => write!(f, "SIGRTMAX"),
+            Self::Realtime(n) => write!(f, "SIGRTMIN+{n}"),
+        }
+    }
+}
+
+/// Result of sending a signal to a single process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignalOutcome {
+    Sent,
+    /// `ESRCH`
+    NoSuchProcess,
+    /// `EPERM`
+    PermissionDenied,
+    /// `EINVAL`
+    InvalidSignal,
+    /// The pid now belongs to a different process than the one that was staged.
+    AlreadyGone,
+    Other(String),
+}
+
+impl SignalOutcome {
+    pub fn is_sent(&self) -> bool {
+        *self == Self::Sent
+    }
+}
+
+impl From<std::io::Result<()>> for SignalOutcome {
+    fn from(result: std::io::Result<()>) -> Self {
+        let Err(err) = result else {
+            return Self::Sent;
+        };
+        match err.raw_os_error() {
+            Some(libc::ESRCH) => Self::NoSuchProcess,
+            Some(libc::EPERM | libc::EACCES) => Self::PermissionDenied,
+            Some(libc::EINVAL) => Self::InvalidSignal,
+            _ => Self::Other(err.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for SignalOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Same wording as `bottom::app::process_killer`.
+        match self {
+            Self::Sent => write!(f, "ok"),
+            Self::NoSuchProcess => write!(f, "the target process did not exist"),
+            Self::PermissionDenied => write!(
+                f,
+                "the calling process does not have the permissions to act on the target process"
+            ),
+            Self::InvalidSignal => write!(f, "an invalid signal was specified"),
+            Self::AlreadyGone => write!(f, "process already gone"),
+            Self::Other(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ActionResult {
+    pub key: ProcessKey,
+    pub name: String,
+    pub outcome: SignalOutcome,
+}
+
+/// Owners to warn about before signalling processes, given their uid and user name: root and
+/// anyone other than `my_uid`. Root's processes are flagged even when running as root, as that's
+/// when they can actually be killed.
+pub fn owners_to_warn_about<'a>(
+    owners: impl IntoIterator<Item = (Option<u32>, &'a str)>,
+    my_uid: u32,
+) -> Vec<&'a str> {
+    let mut users: Vec<&str> = owners
+        .into_iter()
+        .filter(|(uid, _)| uid.is_some_and(|uid| uid == 0 || uid != my_uid))
+        .map(|(_, user)| user)
+        .collect();
+    users.sort_unstable();
+    users.dedup();
+    users
+}
+
+/// Results of the last action, e.g. a signal sent with Enter, shown until dismissed with Esc.
+#[derive(Clone, Debug)]
+pub struct ActionResults {
+    /// What was done, e.g. "SIGTERM".
+    pub action: String,
+    /// Outcome of acting on a whole group at once, e.g. writing `cgroup.kill`. `results` then
+    /// just list the processes in the group.
+    pub group_outcome: Option<SignalOutcome>,
+    pub results: Vec<ActionResult>,
+}
+
+impl ActionResults {
+    pub fn count(&self, f: impl Fn(&SignalOutcome) -> bool) -> usize {
+        self.results.iter().filter(|r| f(&r.outcome)).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::Command;
+
+    #[test]
+    fn test_all_signals() {
+        let all = KillSignal::all();
+        assert_eq!(all[0], KillSignal::Named(Signal::Hup));
+        assert!(all.contains(&KillSignal::Named(Signal::Kill)));
+
+        let mut raw: Vec<_> = all.iter().map(|sig| sig.raw()).collect();
+        raw.sort_unstable();
+        raw.dedup();
+        assert_eq!(raw.len(), all.len());
+        assert_eq!(raw.last(), Some(&libc::SIGRTMAX()));
+
+        let names: Vec<_> = all.iter().map(KillSignal::to_string).collect();
+        assert!(names.contains(&"SIGTERM".to_string()));
+        assert!(names.contains(&"SIGRTMIN".to_string()));
+        assert!(names.contains(&"SIGRTMIN+1".to_string()));
+        assert_eq!(names.last().map(String::as_str), Some("SIGRTMAX"));
+    }
+
+    #[test]
+    fn test_pauses() {
+        assert_eq!(KillSignal::Named(Signal::Stop).pauses(), Some(true));
+        assert_eq!(KillSignal::Named(Signal::Cont).pauses(), Some(false));
+        assert_eq!(KillSignal::Named(Signal::Term).pauses(), None);
+        assert_eq!(KillSignal::Realtime(0).pauses(), None);
+    }
+
+    #[test]
+    fn test_send_realtime_signal() {
+        let mut child = Command::new("sleep").arg("60").spawn().unwrap();
+        let pid = child.id() as i32;
+        let start_time = procfs::process::Process::new(pid)
+            .unwrap()
+            .stat()
+            .unwrap()
+            .starttime;
+        let process = ProcessHandle::open(ProcessKey { pid, start_time }, "sleep".to_string());
+
+        // Default action of realtime signals is to terminate.
+        assert!(process.send(KillSignal::Realtime(1)).is_sent());
+        let status = child.wait().unwrap();
+        assert_eq!(status.signal(), Some(libc::SIGRTMIN() + 1));
+    }
+
+    #[test]
+    fn test_outcomes() {
+        let outcome = |errno| SignalOutcome::from(Err(std::io::Error::from_raw_os_error(errno)));
+        assert_eq!(SignalOutcome::from(Ok(())), SignalOutcome::Sent);
+        assert_eq!(outcome(libc::ESRCH), SignalOutcome::NoSuchProcess);
+        assert_eq!(outcome(libc::EPERM), SignalOutcome::PermissionDenied);
+        assert_eq!(outcome(libc::EACCES), SignalOutcome::PermissionDenied);
+        assert_eq!(outcome(libc::EINVAL), SignalOutcome::InvalidSignal);
+        assert!(matches!(outcome(libc::EIO), SignalOutcome::Other(_)));
+
+        let result = |pid, outcome| ActionResult {
+            key: ProcessKey { pid, start_time: 0 },
+            name: "test".to_string(),
+            outcome,
+        };
+        let results = ActionResults {
+            action: "SIGTERM".to_string(),
+            group_outcome: None,
+            results: vec![
+                result(1, SignalOutcome::Sent),
+                result(2, outcome(libc::EPERM)),
+                result(3, SignalOutcome::Sent),
+            ],
+        };
+        assert_eq!(results.count(SignalOutcome::is_sent), 2);
+        assert_eq!(
+            results.count(|outcome| *outcome == SignalOutcome::PermissionDenied),
+            1
+        );
+    }
+
+    #[test]
+    fn test_owners_to_warn_about() {
+        let owners = [
+            (Some(1000), "me"),
+            (Some(0), "root"),
+            (Some(1001), "other"),
+            (Some(0), "root"),
+            (None, "unknown"),
+        ];
+        assert_eq!(owners_to_warn_about(owners, 1000), vec!["other", "root"]);
+        assert_eq!(owners_to_warn_about(owners, 0), vec!["me", "other", "root"]);
+        assert!(owners_to_warn_about([(Some(1000), "me")], 1000).is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+//! Resolving socket inodes from `/proc/<pid>/fd` to TCP/UDP/Unix endpoints.
+
+use procfs::net::{TcpState, UdpState};
+use procfs::process::{FDTarget, Process};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Protocol {
+    Tcp,
+    Tcp6,
+    Udp,
+    Udp6,
+    Unix,
+}
+
+impl Display for Protocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Protocol::Tcp => "tcp",
+            Protocol::Tcp6 => "tcp6",
+            Protocol::Udp => "udp",
+            Protocol::Udp6 => "udp6",
+            Protocol::Unix => "unix",
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Endpoint {
+    Inet {
+        local: SocketAddr,
+        remote: SocketAddr,
+        /// TCP state, e.g. `LISTEN`. UDP sockets are only "connected" or not.
+        state: String,
+        listening: bool,
+    },
+    /// Bound path, if any. Abstract sockets start with `@`.
+    Unix(Option<PathBuf>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Socket {
+    pub protocol: Protocol,
+    pub endpoint: Endpoint,
+}
+
+impl Socket {
+    /// Local port of a TCP/UDP socket.
+    pub fn local_port(&self) -> Option<u16> {
+        match &self.endpoint {
+            Endpoint::Inet { local, .. } => Some(local.port()),
+            Endpoint::Unix(_) => None,
+        }
+    }
+
+    /// Listening TCP socket, or UDP socket bound to a port without a peer.
+    pub fn is_listening(&self) -> bool {
+        matches!(
+            self.endpoint,
+            Endpoint::Inet {
+                listening: true,
+                ..
+            }
+        )
+    }
+}
+
+impl Display for Socket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.endpoint {
+            Endpoint::Inet { local, state, .. } if self.is_listening() => {
+                write!(f, "{} {local} {state}", self.protocol)
+            }
+            Endpoint::Inet {
+                local,
+                remote,
+                state,
+                ..
+            } => write!(f, "{} {local} → {remote} {state}", self.protocol),
+            Endpoint::Unix(Some(path)) => write!(f, "unix {}", path.display()),
+            Endpoint::Unix(None) => f.write_str("unix (unnamed)"),
+        }
+    }
+}
+
+/// Sockets of one network namespace, by inode.
+#[derive(Clone, Debug, Default)]
+pub struct SocketTable {
+    sockets: HashMap<u64, Socket>,
+}
+
+impl SocketTable {
+    /// Reads socket tables of the network namespace `ps` lives in.
+    /// Tables that can't be read (e.g. no IPv6) are skipped.
+    pub fn read(ps: &Process) -> Self {
+        let mut sockets = HashMap::new();
+
+        let tcp = [(Protocol::Tcp, ps.tcp()), (Protocol::Tcp6, ps.tcp6())];
+        for (protocol, entries) in tcp {
+            for entry in entries.into_iter().flatten() {
+                let endpoint = Endpoint::Inet {
+                    local: entry.local_address,
+                    remote: entry.remote_address,
+                    listening: entry.state == TcpState::Listen,
+                    state: format!("{:?}", entry.state).to_uppercase(),
+                };
+                sockets.insert(entry.inode, Socket { protocol, endpoint });
+            }
+        }
+
+        let udp = [(Protocol::Udp, ps.udp()), (Protocol::Udp6, ps.udp6())];
+        for (protocol, entries) in udp {
+            for entry in entries.into_iter().flatten() {
+                let connected = entry.state == UdpState::Established;
+                let endpoint = Endpoint::Inet {
+                    local: entry.local_address,
+                    remote: entry.remote_address,
+                    listening: !connected,
+                    state: if connected { "CONNECTED" } else { "UNCONN" }.to_string(),
+                };
+                sockets.insert(entry.inode, Socket { protocol, endpoint });
+            }
+        }
+
+        for entry in ps.unix().into_iter().flatten() {
+            let socket = Socket {
+                protocol: Protocol::Unix,
+                endpoint: Endpoint::Unix(entry.path),
+            };
+            sockets.insert(entry.inode, socket);
+        }
+
+        Self { sockets }
+    }
+
+    pub fn get(&self, inode: u64) -> Option<&Socket> {
+        self.sockets.get(&inode)
+    }
+}
+
+/// Local ports of TCP/UDP sockets held by each process, for the `port:` and `listen:` searches.
+#[derive(Clone, Debug, Default)]
+pub struct PortOwners {
+    /// Port and whether the socket is listening, by pid.
+    ports: HashMap<i32, Vec<(u16, bool)>>,
+}
+
+impl PortOwners {
+    /// Joins socket tables of killa's network namespace with fd tables of all processes.
+    /// Processes of other users are only visible with elevated privileges.
+    pub fn read() -> Self {
+        let table = Process::myself()
+            .map(|me| SocketTable::read(&me))
+            .unwrap_or_default();
+
+        let mut ports = HashMap::new();
+        for ps in procfs::process::all_processes()
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            let Ok(fds) = ps.fd() else {
+                continue;
+            };
+            let owned: Vec<_> = fds
+                .flatten()
+                .filter_map(|fd| match fd.target {
+                    FDTarget::Socket(inode) => table.get(inode),
+                    _ => None,
+                })
+                .filter_map(|socket| Some((socket.local_port()?, socket.is_listening())))
+                .collect();
+            if !owned.is_empty() {
+                ports.insert(ps.pid(), owned);
+            }
+        }
+        Self { ports }
+    }
+
+    /// Local ports of sockets of `pid`, optionally only listening ones.
+    pub fn ports(&self, pid: i32, listening_only: bool) -> impl Iterator<Item = u16> + '_ {
+        self.ports
+            .get(&pid)
+            .into_iter()
+            .flatten()
+            .filter(move |(_, listening)| !listening_only || *listening)
+            .map(|(port, _)| *port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{TcpListener, UdpSocket};
+
+    #[test]
+    fn test_resolve_own_listener() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let me = Process::myself().unwrap();
+        let table = SocketTable::read(&me);
+        let sockets: Vec<_> = me
+            .fd()
+            .unwrap()
+            .flatten()
+            .filter_map(|fd| match fd.target {
+                FDTarget::Socket(inode) => table.get(inode).map(Socket::to_string),
+                _ => None,
+            })
+            .collect();
+        assert!(sockets.contains(&format!("tcp 127.0.0.1:{port} LISTEN")));
+    }
+
+    #[test]
+    fn test_port_owners() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp_port = listener.local_addr().unwrap().port();
+        let udp = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let udp_port = udp.local_addr().unwrap().port();
+        let client = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = std::net::TcpStream::connect(client.local_addr().unwrap()).unwrap();
+        let client_port = stream.local_addr().unwrap().port();
+
+        let owners = PortOwners::read();
+        let pid = std::process::id() as i32;
+        let listening: Vec<_> = owners.ports(pid, true).collect();
+        let all: Vec<_> = owners.ports(pid, false).collect();
+        assert!(listening.contains(&tcp_port));
+        assert!(listening.contains(&udp_port));
+        assert!(!listening.contains(&client_port));
+        assert!(all.contains(&client_port));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::details::ProcessDetails;
+use crate::signal::KillSignal;
 use crate::ui::ColumnKind;
 
 /// Messages that update UI.
@@ -10,9 +12,64 @@ pub enum Message {
     ToggleFreeze,
     Freeze(bool),
     ToggleWireframe(bool),
+    /// Sort by the column, or reverse the order if already sorted by it.
     SetSortField(ColumnKind),
+    ReverseSortOrder,
+    ToggleTreeMode,
+    /// Expand or collapse children of the process with given pid.
+    ToggleCollapsed(i32),
     Search(TextInputAction),
-    StageSignalAllFiltered(rustix::process::Signal),
+    /// Stage signal for the selected row, or all filtered rows if there's no selection.
+    StageSignal(KillSignal),
+    /// Stage signal for the selected row and all of its descendants.
+    StageSignalSubtree(KillSignal),
+    /// Stage signal for the whole cgroup of the selected row. SIGKILL, SIGSTOP and SIGCONT use
+    /// `cgroup.kill` and `cgroup.freeze`, so that processes can't escape in the meantime.
+    StageSignalCgroup(KillSignal),
+    /// Stage SIGTERM, followed by SIGKILL for processes that don't exit within the grace period.
+    StageEscalatingKill,
+    /// Change the escalating kill grace period by given number of seconds.
+    ChangeGracePeriod(i64),
+    EscalationTick(std::time::Instant),
+    /// Send SIGCONT to all processes paused with SIGSTOP in this session.
+    ResumeAllPaused,
+    /// Open the priority dialog for the same processes [`Message::StageSignal`] would target.
+    OpenPriorityDialog,
+    SetNice(i32),
+    SetIoClass(crate::priority::IoClass),
+    SetIoLevel(u8),
+    /// Open the CPU affinity dialog for the same processes [`Message::StageSignal`] would target.
+    OpenAffinityDialog,
+    SetAffinityText(String),
+    SetAffinityAllThreads(bool),
+    /// Ask for a path, then search for processes using it.
+    OpenPathUsersDialog,
+    SetPathUsersText(String),
+    /// Open the column chooser, e.g. by right-clicking the table header.
+    OpenColumnChooser,
+    /// Read the details panel again, it's not refreshed with the table.
+    RefreshDetails,
+    /// Read details of the process, if it's still selected after a short delay.
+    LoadDetails(i32),
+    /// Details read in the background, see [`Message::LoadDetails`].
+    DetailsLoaded(ProcessDetails),
+    /// Show or hide a column.
+    ToggleColumn(ColumnKind, bool),
+    /// Move a column left (negative) or right (positive).
+    MoveColumn(ColumnKind, isize),
+    SetColumnWidth(ColumnKind, f32),
+    /// Save columns to the config, after changing a width is done.
+    SaveColumns,
+    ResetColumns,
+    /// Open the signal picker for the same processes [`Message::StageSignal`] would target.
+    OpenSignalPicker,
+    /// Signal chosen in the signal picker.
+    PickSignal(KillSignal),
+    MoveSelection(SelectionMove),
+    /// Row clicked. With Ctrl held, this toggles the mark instead.
+    Select(i32),
+    ToggleMarkSelected,
+    ModifiersChanged(iced::keyboard::Modifiers),
     /// Escape key or back button pressed.
     Back,
     /// Enter pressed.
@@ -28,3 +85,50 @@ pub enum TextInputAction {
     Toggle,
     Hide,
 }
+
+#[derive(Debug, Clone, Copy)]
+pub enum SelectionMove {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+}
+
+impl SelectionMove {
+    /// Index of the row to select in a list of `last + 1` rows, moving from `current`.
+    /// Without a current row, the first one is selected.
+    pub fn apply(self, current: Option<usize>, last: usize, page_rows: usize) -> usize {
+        match (self, current) {
+            (SelectionMove::Home, _) | (_, None) => 0,
+            (SelectionMove::End, _) => last,
+            (SelectionMove::Up, Some(i)) => i.saturating_sub(1),
+            (SelectionMove::Down, Some(i)) => (i + 1).min(last),
+            (SelectionMove::PageUp, Some(i)) => i.saturating_sub(page_rows),
+            (SelectionMove::PageDown, Some(i)) => i.saturating_add(page_rows).min(last),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_move() {
+        let apply = |movement: SelectionMove, current| movement.apply(current, 30, 20);
+        assert_eq!(apply(SelectionMove::Down, None), 0);
+        assert_eq!(apply(SelectionMove::End, None), 0);
+        assert_eq!(apply(SelectionMove::Down, Some(5)), 6);
+        assert_eq!(apply(SelectionMove::Down, Some(30)), 30);
+        assert_eq!(apply(SelectionMove::Up, Some(0)), 0);
+        assert_eq!(apply(SelectionMove::Up, Some(5)), 4);
+        assert_eq!(apply(SelectionMove::PageDown, Some(5)), 25);
+        assert_eq!(apply(SelectionMove::PageDown, Some(25)), 30);
+        assert_eq!(apply(SelectionMove::PageUp, Some(25)), 5);
+        assert_eq!(apply(SelectionMove::PageUp, Some(5)), 0);
+        assert_eq!(apply(SelectionMove::Home, Some(5)), 0);
+        assert_eq!(apply(SelectionMove::End, Some(5)), 30);
+    }
+}
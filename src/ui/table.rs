@@ -1,32 +1,115 @@
 use crate::Message;
+use crate::affinity::{self, CpuList};
+use crate::priority::IoPriority;
+use crate::process_data::ProcessKey;
+use bytesize::ByteSize;
+use chrono::{DateTime, Local};
 use iced::widget::tooltip::Position;
-use iced::widget::{container, text, tooltip};
-use iced::{Element, Length, Pixels, Renderer, Theme};
+use iced::widget::{container, mouse_area, row, text, tooltip};
+use iced::{Element, Font, Length, Pixels, Renderer, Theme, mouse};
 use std::fmt::Display;
 use std::time::Duration;
 
 /// Actual storage for data row.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct Row {
     //  todo: get this out?
     pub row_index: usize, // todo: get this out?
     pub program_name: String,
     pub program_name_lowercase: String, // index for search
     pub mem: u64,
+    pub mem_perc: f32,
+    /// Swapped out memory in bytes.
+    pub swap: Option<u64>,
     pub cpu_perc: f32,
     pub pid: i32,
     pub command: String,
     pub command_lowercase: String, // index for search
+    pub user: String,
+    /// Disk I/O in bytes.
+    pub read_per_sec: u64,
+    pub write_per_sec: u64,
+    pub total_read: u64,
+    pub total_write: u64,
+    pub threads: u64,
+    /// Controlling terminal, e.g. `pts/3`.
+    pub tty: Option<String>,
+    /// Time since the process started.
+    pub age: Duration,
+    /// Time spent on CPU, in user and kernel mode.
     pub cpu_time: Duration,
-    // start_time: Option<chrono::DateTime<chrono::Local>>,
+    /// CPU time of children that have exited and were waited for.
+    pub children_cpu_time: Duration,
+    /// Process state name, e.g. "Sleeping".
+    pub state_name: String,
+    /// Process state as shown by `ps`, e.g. `T` for stopped.
+    pub state: char,
+    pub nice: i32,
+    /// Not available for processes of other users, unless running as root.
+    pub io_priority: Option<IoPriority>,
+    /// CPUs the process is allowed to run on.
+    pub affinity: Option<CpuList>,
+    pub parent_pid: Option<i32>,
+    /// Set only when rows are laid out as a process tree.
+    pub tree: Option<TreeNode>,
+    pub is_selected: bool,
+    pub is_marked: bool,
+    /// See [`ProcessKey::start_time`].
+    pub start_ticks: u64,
+    pub started_at: Option<DateTime<Local>>,
+}
+
+/// Position of a row within the process tree.
+#[derive(Clone, Debug)]
+pub struct TreeNode {
+    /// Branch drawing that goes before the name, e.g. `│  ├─ `.
+    pub prefix: String,
+    pub has_children: bool,
+    pub is_collapsed: bool,
 }
 
 impl Row {
+    /// Stopped by a signal, e.g. paused with SIGSTOP.
+    pub fn is_stopped(&self) -> bool {
+        self.state == 'T'
+    }
+
+    pub fn key(&self) -> ProcessKey {
+        ProcessKey {
+            pid: self.pid,
+            start_time: self.start_ticks,
+        }
+    }
+
     pub fn cell(self, for_column: &ColumnKind) -> Element<'_, Message, Theme, Renderer> {
         let font_size = Pixels::from(13.0);
         let tooltip_font_size = Pixels::from(11.0);
         let content: Element<_> = match for_column {
-            ColumnKind::Name => text!("{}", self.program_name).size(font_size).into(),
+            ColumnKind::Name => match &self.tree {
+                None => text!("{}", self.program_name).size(font_size).into(),
+                Some(node) => {
+                    let marker: Element<_> = if node.has_children {
+                        mouse_area(
+                            text(if node.is_collapsed { "[+]" } else { "[-]" })
+                                .font(Font::MONOSPACE)
+                                .size(font_size),
+                        )
+                        .on_press(Message::ToggleCollapsed(self.pid))
+                        .interaction(mouse::Interaction::Pointer)
+                        .into()
+                    } else {
+                        text("   ").font(Font::MONOSPACE).size(font_size).into()
+                    };
+                    row![
+                        text!("{}", node.prefix)
+                            .font(Font::MONOSPACE)
+                            .size(font_size),
+                        marker,
+                        text!(" {}", self.program_name).size(font_size),
+                    ]
+                    .into()
+                }
+            },
             ColumnKind::Memory => text!("{} MB", self.mem).size(font_size).into(),
             ColumnKind::Cpu => if self.cpu_perc != 0.0 {
                 text!("{:.1} %", self.cpu_perc)
@@ -35,56 +118,189 @@ impl Row {
             }
             .size(font_size)
             .into(),
+            ColumnKind::MemoryPercent => text!("{:.1} %", self.mem_perc).size(font_size).into(),
+            ColumnKind::Swap => match self.swap {
+                Some(swap) => text(format_bytes(swap)),
+                None => text!("?"),
+            }
+            .size(font_size)
+            .into(),
             ColumnKind::Pid => text!("{}", self.pid).size(font_size).into(),
+            ColumnKind::ParentPid => match self.parent_pid {
+                Some(ppid) => text!("{ppid}"),
+                None => text!("—"),
+            }
+            .size(font_size)
+            .into(),
+            ColumnKind::User => text!("{}", self.user).size(font_size).into(),
+            ColumnKind::Threads => text!("{}", self.threads).size(font_size).into(),
+            ColumnKind::ReadRate => text(format_rate(self.read_per_sec)).size(font_size).into(),
+            ColumnKind::WriteRate => text(format_rate(self.write_per_sec)).size(font_size).into(),
+            ColumnKind::TotalRead => text(format_bytes(self.total_read)).size(font_size).into(),
+            ColumnKind::TotalWrite => text(format_bytes(self.total_write)).size(font_size).into(),
+            ColumnKind::Tty => text!("{}", self.tty.as_deref().unwrap_or("—"))
+                .size(font_size)
+                .into(),
+            ColumnKind::Nice => text!("{}", self.nice).size(font_size).into(),
+            ColumnKind::IoPriority => match self.io_priority {
+                Some(io_priority) => text!("{io_priority}"),
+                None => text!("?"),
+            }
+            .size(font_size)
+            .into(),
+            ColumnKind::Affinity => match &self.affinity {
+                Some(cpus) if cpus.is_all(affinity::num_cpus()) => text!("all"),
+                Some(cpus) => text!("{cpus}"),
+                None => text!("?"),
+            }
+            .size(font_size)
+            .into(),
+            ColumnKind::State => if self.is_stopped() {
+                text!("{}", self.state_name).style(text::warning)
+            } else {
+                text!("{}", self.state_name)
+            }
+            .size(font_size)
+            .into(),
             ColumnKind::Command => tooltip(
                 text!("{}", self.command).size(font_size),
                 container(text!("{}", self.command).size(tooltip_font_size))
                     .padding(10)
-                    .style(|theme: &Theme| {
-                        let palette = theme.extended_palette();
-                        let color_set = palette.background.weak;
-                        let alpha = 0.99;
-                        iced::widget::container::Style {
-                            background: Some(color_set.color.scale_alpha(alpha).into()),
-                            text_color: Some(color_set.text.scale_alpha(alpha)),
-                            border: iced::border::rounded(5)
-                                .color(palette.secondary.weak.color)
-                                .width(1.5),
-                            ..Default::default()
-                        }
-                    })
+                    .style(tooltip_style)
                     .max_width(700),
                 Position::Bottom,
             )
             .into(),
-            ColumnKind::Started => text!("{}", {
-                // TODO: optimize for startup times.
-                // Current implementation is pretty detrimental with the Mutex, it probably adds like 100ms.
-                // Maybe pre-fetch the process start times?
-                // get_process_start_time(row.pid)
-                //     .unwrap_or_default()
-                //     .format("%d/%m/%Y %H:%M")
-                "-"
-            })
-            .size(font_size)
-            .into(),
-            ColumnKind::CpuTime => text!("{}", humantime::format_duration(self.cpu_time))
+            ColumnKind::Started => match self.started_at {
+                Some(started_at) => tooltip(
+                    text(format_ago(Local::now() - started_at)).size(font_size),
+                    container(
+                        text!("{}", started_at.format("%Y-%m-%d %H:%M:%S")).size(tooltip_font_size),
+                    )
+                    .padding(10)
+                    .style(tooltip_style),
+                    Position::Bottom,
+                )
+                .into(),
+                None => text!("?").size(font_size).into(),
+            },
+            ColumnKind::Age => text!("{}", humantime::format_duration(self.age))
                 .size(font_size)
                 .into(),
+            ColumnKind::CpuTime if self.children_cpu_time.is_zero() => {
+                text(format_cpu_time(self.cpu_time)).size(font_size).into()
+            }
+            ColumnKind::CpuTime => tooltip(
+                text(format_cpu_time(self.cpu_time)).size(font_size),
+                container(
+                    text!(
+                        "{} including exited children",
+                        format_cpu_time(self.cpu_time + self.children_cpu_time)
+                    )
+                    .size(tooltip_font_size),
+                )
+                .padding(10)
+                .style(tooltip_style),
+                Position::Bottom,
+            )
+            .into(),
         };
 
-        container(content).width(Length::Fill).center_y(15).into()
+        let pid = self.pid;
+        let is_selected = self.is_selected;
+        let is_marked = self.is_marked;
+        mouse_area(container(content).width(Length::Fill).center_y(15).style(
+            move |theme: &Theme| {
+                let palette = theme.extended_palette();
+                let pair = match (is_selected, is_marked) {
+                    (true, _) => palette.primary.weak,
+                    (false, true) => palette.secondary.strong,
+                    (false, false) => return container::Style::default(),
+                };
+                container::Style {
+                    background: Some(pair.color.into()),
+                    text_color: Some(pair.text),
+                    ..Default::default()
+                }
+            },
+        ))
+        .on_press(Message::Select(pid))
+        .into()
+    }
+}
+
+fn tooltip_style(theme: &Theme) -> container::Style {
+    let palette = theme.extended_palette();
+    let color_set = palette.background.weak;
+    let alpha = 0.99;
+    container::Style {
+        background: Some(color_set.color.scale_alpha(alpha).into()),
+        text_color: Some(color_set.text.scale_alpha(alpha)),
+        border: iced::border::rounded(5)
+            .color(palette.secondary.weak.color)
+            .width(1.5),
+        ..Default::default()
+    }
+}
+
+/// Time since an event in the largest whole unit, e.g. "3h ago".
+fn format_ago(elapsed: chrono::TimeDelta) -> String {
+    let secs = elapsed.num_seconds().max(0);
+    match secs {
+        0..60 => format!("{secs}s ago"),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+/// CPU time like in `ps`, e.g. `1:02:03`, or seconds when it's under a minute.
+fn format_cpu_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    match secs {
+        0..60 => format!("{:.1}s", time.as_secs_f32()),
+        60..3600 => format!("{}:{:02}", secs / 60, secs % 60),
+        _ => format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60),
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0 => "—".to_string(),
+        bytes => ByteSize(bytes).to_string(),
+    }
+}
+
+fn format_rate(bytes_per_sec: u64) -> String {
+    match bytes_per_sec {
+        0 => "—".to_string(),
+        bytes => format!("{}/s", ByteSize(bytes)),
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ColumnKind {
     Name,
     Memory,
+    MemoryPercent,
+    Swap,
     Cpu,
     Pid,
+    ParentPid,
+    User,
+    State,
+    Threads,
+    Nice,
+    IoPriority,
+    Affinity,
+    ReadRate,
+    WriteRate,
+    TotalRead,
+    TotalWrite,
+    Tty,
     Command,
     Started,
+    Age,
     CpuTime,
 }
 
@@ -93,9 +309,24 @@ impl Display for ColumnKind {
         match self {
             ColumnKind::Name => f.write_str("Name"),
             ColumnKind::Memory => f.write_str("Memory"),
+            ColumnKind::MemoryPercent => f.write_str("Mem %"),
+            ColumnKind::Swap => f.write_str("Swap"),
             ColumnKind::Cpu => f.write_str("CPU"),
             ColumnKind::Pid => f.write_str("ID"),
-            ColumnKind::CpuTime => f.write_str("Time"),
+            ColumnKind::ParentPid => f.write_str("Parent"),
+            ColumnKind::User => f.write_str("User"),
+            ColumnKind::State => f.write_str("State"),
+            ColumnKind::Threads => f.write_str("Threads"),
+            ColumnKind::Nice => f.write_str("Nice"),
+            ColumnKind::IoPriority => f.write_str("IO-Prio"),
+            ColumnKind::Affinity => f.write_str("Affinity"),
+            ColumnKind::ReadRate => f.write_str("Read/s"),
+            ColumnKind::WriteRate => f.write_str("Write/s"),
+            ColumnKind::TotalRead => f.write_str("Read"),
+            ColumnKind::TotalWrite => f.write_str("Written"),
+            ColumnKind::Tty => f.write_str("TTY"),
+            ColumnKind::Age => f.write_str("Age"),
+            ColumnKind::CpuTime => f.write_str("CPU Time"),
             ColumnKind::Started => f.write_str("Started"),
             ColumnKind::Command => f.write_str("Command"),
         }
@@ -103,15 +334,124 @@ impl Display for ColumnKind {
 }
 
 impl ColumnKind {
+    /// Columns offered by the column chooser.
+    pub const ALL: [ColumnKind; 22] = [
+        ColumnKind::Name,
+        ColumnKind::Memory,
+        ColumnKind::MemoryPercent,
+        ColumnKind::Swap,
+        ColumnKind::Cpu,
+        ColumnKind::Pid,
+        ColumnKind::ParentPid,
+        ColumnKind::User,
+        ColumnKind::State,
+        ColumnKind::Threads,
+        ColumnKind::Nice,
+        ColumnKind::IoPriority,
+        ColumnKind::Affinity,
+        ColumnKind::ReadRate,
+        ColumnKind::WriteRate,
+        ColumnKind::TotalRead,
+        ColumnKind::TotalWrite,
+        ColumnKind::Tty,
+        ColumnKind::Started,
+        ColumnKind::Age,
+        ColumnKind::CpuTime,
+        ColumnKind::Command,
+    ];
+
+    /// Name used in the config file.
+    pub fn id(&self) -> &'static str {
+        match self {
+            ColumnKind::Name => "name",
+            ColumnKind::Memory => "memory",
+            ColumnKind::MemoryPercent => "memory-percent",
+            ColumnKind::Swap => "swap",
+            ColumnKind::Cpu => "cpu",
+            ColumnKind::Pid => "pid",
+            ColumnKind::ParentPid => "ppid",
+            ColumnKind::User => "user",
+            ColumnKind::State => "state",
+            ColumnKind::Threads => "threads",
+            ColumnKind::Nice => "nice",
+            ColumnKind::IoPriority => "io-priority",
+            ColumnKind::Affinity => "affinity",
+            ColumnKind::ReadRate => "read-rate",
+            ColumnKind::WriteRate => "write-rate",
+            ColumnKind::TotalRead => "total-read",
+            ColumnKind::TotalWrite => "total-write",
+            ColumnKind::Tty => "tty",
+            ColumnKind::Command => "command",
+            ColumnKind::Started => "started",
+            ColumnKind::Age => "age",
+            ColumnKind::CpuTime => "cpu-time",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.id() == id)
+    }
+
+    /// Default width, as a share of the table width.
     pub fn width_ratio(&self) -> f32 {
         match self {
             ColumnKind::Name => 0.35,
             ColumnKind::Memory => 0.1,
+            ColumnKind::MemoryPercent => 0.06,
+            ColumnKind::Swap => 0.07,
             ColumnKind::Cpu => 0.06,
             ColumnKind::Pid => 0.08,
+            ColumnKind::ParentPid => 0.06,
+            ColumnKind::User => 0.08,
+            ColumnKind::State => 0.08,
+            ColumnKind::Threads => 0.05,
+            ColumnKind::Nice => 0.05,
+            ColumnKind::IoPriority => 0.05,
+            ColumnKind::Affinity => 0.06,
+            ColumnKind::ReadRate => 0.07,
+            ColumnKind::WriteRate => 0.07,
+            ColumnKind::TotalRead => 0.07,
+            ColumnKind::TotalWrite => 0.07,
+            ColumnKind::Tty => 0.05,
             ColumnKind::Command => 0.4,
-            ColumnKind::CpuTime => 0.1,
+            ColumnKind::Age => 0.1,
+            ColumnKind::CpuTime => 0.08,
             ColumnKind::Started => 0.1,
         }
     }
 }
+
+/// Column shown in the process table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Column {
+    pub kind: ColumnKind,
+    /// Share of the table width. Columns are scaled to fill the table, so this is relative
+    /// to other visible columns.
+    pub width: f32,
+}
+
+impl Column {
+    pub const MIN_WIDTH: f32 = 0.02;
+    pub const MAX_WIDTH: f32 = 0.5;
+
+    pub fn new(kind: ColumnKind) -> Self {
+        Self {
+            kind,
+            width: kind.width_ratio(),
+        }
+    }
+
+    /// Columns shown when there's nothing in the config.
+    pub fn defaults() -> Vec<Column> {
+        [
+            ColumnKind::Name,
+            ColumnKind::Memory,
+            ColumnKind::Cpu,
+            ColumnKind::Pid,
+            ColumnKind::Command,
+        ]
+        .into_iter()
+        .map(Column::new)
+        .collect()
+    }
+}